@@ -10,6 +10,20 @@ pub static TOKEN_ACCOUNT: &[u8] = b"token_account";
 
 pub static TOKEN_PURCHASE: &[u8] = b"token_purchase";
 
+pub static CONFIG_SEED: &[u8] = b"platform_config";
+
+pub static TREASURY_SEED: &[u8] = b"treasury";
+
+pub static LP_LOCK_SEED: &[u8] = b"lp_lock";
+
+pub static SOL_VAULT_SEED: &[u8] = b"sol_vault";
+
+//手续费费率分母（万分比）
+pub static FEE_DENOMINATOR: u64 = 10_000;
+
+//Raydium建池时创建的LP Mint及代币账户按此空间预估租金（含Token-2022扩展余量）
+pub static POOL_ACCOUNT_SPACE: usize = 300;
+
 
 
 // pub static Stake_CA: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"; //质押合约
@@ -12,7 +12,19 @@ use structures::{
     withdraw_tokens::*,
     buy_token::*,
     withdraw_sale_tokens::*,
+    init_config::*,
+    update_config::*,
+    change_admin::*,
+    withdraw_treasury::*,
+    set_pause::*,
+    migrate_liquidity::*,
+    release_lp::*,
+    create_token_and_sale::*,
+    finalize_sale::*,
+    cancel_sale::*,
+    close_sale::*,
 };
+use structures::{ConfigParams, SaleParams};
 
 declare_id!("8u2V6SHBURgDV23rvWFKBvPvhthYKP3eHfYgGJzQHLps");
 
@@ -23,24 +35,100 @@ pub mod sell_token {
     // pub fn sale_account(ctx: Context<InitSaleAccount>, sale_amount: u64, price_per_token: u64, end_time: i64) -> Result<()> {
     //     ctx.accounts.process(sale_amount, price_per_token, end_time)
     // }
-    pub fn init_sale_account(ctx: Context<InitSaleAccount>, sale_amount: u64, price_per_token: u64, end_time: i64) -> Result<()> {
-        ctx.accounts.process(sale_amount, price_per_token, end_time)
+    pub fn init_sale_account(ctx: Context<InitSaleAccount>, params: SaleParams) -> Result<()> {
+        ctx.accounts.process(params)
     }
 
-    pub fn buy_token(ctx: Context<BuyToken>, amount: u64,open_time: u64) -> Result<()> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_token_and_sale(
+        ctx: Context<CreateTokenAndSale>,
+        params: SaleParams,
+        _nonce: u64,
+        _decimals: u8,
+        total_supply: u64,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
         let bump = ctx.bumps.pda_account;
-        ctx.accounts.process(amount, bump,open_time)
+        ctx.accounts.process(params, total_supply, name, symbol, uri, bump)
     }
 
-    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>) -> Result<()> {
+    pub fn buy_token(ctx: Context<BuyToken>, amount: u64) -> Result<()> {
+        ctx.accounts.process(amount)
+    }
+
+    pub fn finalize_sale(ctx: Context<FinalizeSale>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn cancel_sale(ctx: Context<CancelSale>) -> Result<()> {
         let bump = ctx.bumps.pda_account;
         ctx.accounts.process(bump)
     }
 
+    pub fn migrate_liquidity(ctx: Context<MigrateLiquidity>) -> Result<()> {
+        let bump = ctx.bumps.pda_account;
+        let lp_lock_bump = ctx.bumps.lp_lock;
+        let sol_vault_bump = ctx.bumps.sol_vault;
+        ctx.accounts.process(bump, lp_lock_bump, sol_vault_bump)
+    }
+
+    pub fn release_lp(ctx: Context<ReleaseLp>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>) -> Result<()> {
+        let bump = ctx.bumps.pda_account;
+        let sol_vault_bump = ctx.bumps.sol_vault;
+        ctx.accounts.process(bump, sol_vault_bump)
+    }
+
     pub fn withdraw_sale_tokens(ctx: Context<WithdrawSaleTokens>) -> Result<()> {
         let bump = ctx.bumps.pda_account;
         ctx.accounts.process(bump)
     }
+
+    pub fn close_sale(ctx: Context<CloseSale>) -> Result<()> {
+        let bump = ctx.bumps.pda_account;
+        let sol_vault_bump = ctx.bumps.sol_vault;
+        ctx.accounts.process(bump, sol_vault_bump)
+    }
+
+    pub fn initialize_config(ctx: Context<InitConfig>, params: ConfigParams) -> Result<()> {
+        let bump = ctx.bumps.config;
+        ctx.accounts.process(params, bump)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        ctx.accounts.process(params)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.process(new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        let bump = ctx.bumps.treasury;
+        ctx.accounts.process(amount, bump)
+    }
+
+    pub fn set_pause(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        ctx.accounts.process(paused)
+    }
+
+    pub fn set_sale_pause(ctx: Context<SetSalePause>, paused: bool) -> Result<()> {
+        ctx.accounts.process(paused)
+    }
+
+    pub fn withdraw_treasury_sol(ctx: Context<WithdrawTreasurySol>, amount: u64) -> Result<()> {
+        let bump = ctx.bumps.treasury_vault;
+        ctx.accounts.process(amount, bump)
+    }
 }
 
 
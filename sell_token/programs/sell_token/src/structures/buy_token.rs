@@ -1,23 +1,18 @@
-use super::SaleAccount;
+use super::{SaleAccount, SaleStatus};
+use super::PlatformConfig;
 use super::UserPurchase;
+use super::net_transfer_amount;
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Token,Mint,TokenAccount},
-    token_interface::{ TokenInterface,Transfer,transfer},
-};
-
-use raydium_cp_swap::{
-    cpi,
-    program::RaydiumCpSwap,
-    states::{AmmConfig, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED},
+    token_interface::{Mint,TokenAccount,TokenInterface,TransferChecked,transfer_checked},
 };
 
 use super::error::ErrorCode;
 
 #[derive(Accounts)]
-#[instruction(bump: u8)]
 pub struct BuyToken<'info> {
     #[account(
         mut,
@@ -28,275 +23,267 @@ pub struct BuyToken<'info> {
 /// CHECK:` doc comment explaining why no checks through types are necessary.
     #[account(
         mut,
-        seeds = [crate::TOKEN_SEED], 
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
         bump,
     )]
-    pub pda_account: AccountInfo<'info>, //合约pda账户
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, PlatformConfig>>,
     
     #[account(
         mut,
         constraint = token_mint.key() == sale.token_mint,
     )]
-    pub token_mint: Box<Account<'info, Mint>>,
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
     
     #[account(
         mut,
         constraint = buy_token_mint.key() == sale.buy_token_mint,
+        mint::token_program = buy_token_program,
     )]
-    pub buy_token_mint: Box<Account<'info, Mint>>,
+    pub buy_token_mint: Box<InterfaceAccount<'info, Mint>>,
     
     #[account(mut)]
     pub buyer: Signer<'info>,
     
+    /// 买家的购买代币账户，原生SOL销售时不需要
     #[account(
         mut,
         token::mint = buy_token_mint,
         token::authority = buyer,
     )]
-    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    pub buyer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
     
     #[account(
         mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
         token::mint = buy_token_mint,
         token::authority = pda_account,
     )]
-    pub sale_token_account: Box<Account<'info, TokenAccount>>,
-    
-    #[account(
-        mut,
-        constraint = sale_sell_token_account.owner == pda_account.key(),
-        constraint = sale_sell_token_account.mint == token_mint.key()
-    )]
-    pub sale_sell_token_account: Account<'info, TokenAccount>,
-
-    // Raydium accounts
-    pub cp_swap_program: Program<'info, RaydiumCpSwap>,
-    
-    pub amm_config: Box<Account<'info, AmmConfig>>,
-
-    /// CHECK: Authority is a PDA owned by Raydium
-    #[account(
-        seeds = [
-            raydium_cp_swap::AUTH_SEED.as_bytes(),
-        ],
-        seeds::program = cp_swap_program.key(),
-        bump,
-    )]
-    pub authority: UncheckedAccount<'info>,
-
-    /// CHECK: Pool state is initialized by Raydium
-    #[account(
-        mut,
-        seeds = [
-            POOL_SEED.as_bytes(),
-            amm_config.key().as_ref(),
-            token_mint.key().as_ref(),
-            buy_token_mint.key().as_ref(),
-        ],
-        seeds::program = cp_swap_program.key(),
-        bump,
-    )]
-    pub pool_state: UncheckedAccount<'info>,
-
-    /// CHECK: LP mint is initialized by Raydium
-    #[account(
-        mut,
-        seeds = [
-            POOL_LP_MINT_SEED.as_bytes(),
-            pool_state.key().as_ref(),
-        ],
-        seeds::program = cp_swap_program.key(),
-        bump,
-    )]
-    pub lp_mint: UncheckedAccount<'info>,
+    pub sale_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// CHECK: Creator LP token account is initialized by Raydium
-    #[account(mut)]
-    pub creator_lp_token: UncheckedAccount<'info>,
-
-    /// CHECK: Token vaults are initialized by Raydium
+    /// CHECK: 原生SOL销售的lamports托管PDA
     #[account(
         mut,
-        seeds = [
-            POOL_VAULT_SEED.as_bytes(),
-            pool_state.key().as_ref(),
-            token_mint.key().as_ref()
-        ],
-        seeds::program = cp_swap_program.key(),
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
         bump,
     )]
-    pub token_0_vault: UncheckedAccount<'info>,
+    pub sol_vault: AccountInfo<'info>,
 
-     /// CHECK: Token vaults are initialized by Raydium
+    /// CHECK: 金库PDA，接收原生SOL手续费并作为手续费代币账户的权限账户
     #[account(
         mut,
-        seeds = [
-            POOL_VAULT_SEED.as_bytes(),
-            pool_state.key().as_ref(),
-            buy_token_mint.key().as_ref()
-        ],
-        seeds::program = cp_swap_program.key(),
+        seeds = [crate::TREASURY_SEED],
         bump,
     )]
-    pub token_1_vault: UncheckedAccount<'info>,
+    pub treasury: AccountInfo<'info>,
 
     #[account(
-        mut,
-        address= raydium_cp_swap::create_pool_fee_reveiver::ID,
-    )]
-    pub create_pool_fee: Box<Account<'info, TokenAccount>>,
-
-    /// CHECK: Observation state is initialized by Raydium
-    #[account(
-        mut,
-        seeds = [
-            OBSERVATION_SEED.as_bytes(),
-            pool_state.key().as_ref(),
-        ],
-        seeds::program = cp_swap_program.key(),
-        bump,
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = buy_token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = buy_token_program,
     )]
-    pub observation_state: UncheckedAccount<'info>,
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        init,
+        init_if_needed,
         payer = buyer,
         space = 8 + core::mem::size_of::<UserPurchase>(),
         seeds = [crate::TOKEN_PURCHASE, buyer.key().as_ref(), token_mint.key().as_ref()],
         bump
     )]
     pub user_purchase: Account<'info, UserPurchase>,
-    pub token_program: Program<'info, Token>,
-    /// Spl token program or token program 2022
-    pub token_0_program: Interface<'info, TokenInterface>,
-    /// Spl token program or token program 2022
-    pub token_1_program: Interface<'info, TokenInterface>,
+    /// 购买代币的代币程序（SPL Token 或 Token-2022）
+    pub buy_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 impl<'info> BuyToken<'info> {
-    pub fn process(&mut self, amount: u64, bump_seed: u8, open_time: u64) -> Result<()> {
+    pub fn process(&mut self, amount: u64) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, false)?;
+
         let current_time = Clock::get()?.unix_timestamp;
+
+        if current_time < self.sale.start_time {
+            return Err(ErrorCode::SaleNotStarted.into());
+        }
         
         if current_time > self.sale.end_time {
             return Err(ErrorCode::SaleEnded.into());
         }
 
+        self.sale.refresh_status(current_time);
+        if self.sale.status != SaleStatus::Active {
+            return Err(ErrorCode::SaleNotActive.into());
+        }
+
         if self.sale.remaining_amount == 0 {
             return Err(ErrorCode::NoTokensLeft.into());
         }
         
-        let token_amount = amount.checked_div(self.sale.price_per_token)
-            .ok_or(ErrorCode::Overflow)?;
+        // 手续费从购买金额中扣除时，先预留出手续费部分
+        let budget = if self.config.mint_fee_on_top {
+            amount
+        } else {
+            amount.checked_sub(self.config.mint_fee(amount)?)
+                .ok_or(ErrorCode::Overflow)?
+        };
+
+        // 价格直接作用于两种代币的最小单位，不再按整币换算
+        let token_amount = self.sale.tokens_for(budget)?;
             
         if token_amount == 0 {
             return Err(ErrorCode::AmountTooSmall.into());
         }
-
-        let decimals = 10u128
-            .checked_pow(self.token_mint.decimals.into())
-            .ok_or(ErrorCode::Overflow)?;
-    
-        let token_amount_u128 = (token_amount as u128)
-            .checked_mul(decimals)
-            .ok_or(ErrorCode::Overflow)?;
     
-        let actual_token_amount = std::cmp::min(token_amount_u128, self.sale.remaining_amount as u128);
-    
-        let amount = actual_token_amount
-            .checked_mul(self.sale.price_per_token as u128)
-            .ok_or(ErrorCode::Overflow)?
-            .checked_div(decimals)
-            .ok_or(ErrorCode::Overflow)?;
+        let actual_token_amount = std::cmp::min(token_amount, self.sale.remaining_amount);
 
-        let actual_amount = u64::try_from(amount)
-            .map_err(|_| ErrorCode::Overflow)?;
+        // 检查单次最小购买量及单地址购买上限
+        self.sale.check_purchase_limits(actual_token_amount, self.user_purchase.token_amount)?;
 
-        transfer(
-            self.into_transfer_to_buyer_context(),
-            actual_amount
-        )?;
+        // 只收取实际购得代币的金额（向上取整），预算中的零头留在买家账户
+        let actual_amount = self.sale.cost_of(actual_token_amount)?;
+
+        // mint手续费按实际支付金额计算
+        let fee_amount = self.config.mint_fee(actual_amount)?;
+
+        // 可退还的手续费先托管在销售金库中，避免平台提取金库后退款失败
+        let fee_refundable = self.config.mint_fee_refundable;
+
+        if self.sale.is_native() {
+            // 原生SOL销售直接转入lamports，无需买家预先包装WSOL
+            self.transfer_lamports(self.sol_vault.to_account_info(), actual_amount)?;
+
+            if fee_amount > 0 {
+                let fee_to = if fee_refundable { &self.sol_vault } else { &self.treasury };
+                self.transfer_lamports(fee_to.to_account_info(), fee_amount)?;
+            }
+        } else {
+            transfer_checked(
+                self.into_transfer_to_buyer_context()?,
+                actual_amount,
+                self.buy_token_mint.decimals
+            )?;
+
+            if fee_amount > 0 {
+                transfer_checked(
+                    self.into_transfer_fee_context(fee_refundable)?,
+                    fee_amount,
+                    self.buy_token_mint.decimals
+                )?;
+            }
+        }
 
         self.sale.remaining_amount = self.sale.remaining_amount
-            .checked_sub(actual_token_amount as u64)
+            .checked_sub(actual_token_amount)
             .ok_or(ErrorCode::Overflow)?;
 
+        // 全部售出后销售成功，流动性迁移由 migrate_liquidity 单独完成
         if self.sale.remaining_amount == 0 {
-            self.sale.is_active = false;
+            self.sale.status = SaleStatus::Succeeded;
+        }
 
-            // Add liquidity to Raydium
-            //amount_0 为代币总量减去sale_amount
-            
-            let amount_0 = self.token_mint.supply
-                .checked_sub(self.sale.sale_amount as u64)
+        msg!("Bought {} tokens for {} lamports, fee {}", actual_token_amount, actual_amount, fee_amount);
+
+        // 购买代币带转账手续费时，按托管账户及金库实际到账的数量记账，退款时才不会超出余额
+        let received_amount = net_transfer_amount(&self.buy_token_mint, actual_amount)?;
+        let received_fee = net_transfer_amount(&self.buy_token_mint, fee_amount)?;
+
+        // 同一钱包可多次购买，购买记录累加
+        let refundable_fee = if fee_refundable { received_fee } else { 0 };
+
+        self.sale.escrowed_fees = self.sale.escrowed_fees
+            .checked_add(refundable_fee)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // 首次购买时新增一条待结算的购买记录
+        if self.user_purchase.purchase_count == 0 {
+            self.sale.open_positions = self.sale.open_positions
+                .checked_add(1)
                 .ok_or(ErrorCode::Overflow)?;
-                
-            let amount_1 = self.sale_token_account.amount as u64;
-
-            let cpi_accounts = cpi::accounts::Initialize {
-                creator: self.pda_account.to_account_info(),
-                amm_config: self.amm_config.to_account_info(),
-                authority: self.authority.to_account_info(),
-                pool_state: self.pool_state.to_account_info(),
-                token_0_mint: self.token_mint.to_account_info(),
-                token_1_mint: self.buy_token_mint.to_account_info(),
-                lp_mint: self.lp_mint.to_account_info(),
-                creator_token_0: self.sale_sell_token_account.to_account_info(),
-                creator_token_1: self.sale_token_account.to_account_info(),
-                creator_lp_token: self.creator_lp_token.to_account_info(),
-                token_0_vault: self.token_0_vault.to_account_info(),
-                token_1_vault: self.token_1_vault.to_account_info(),
-                create_pool_fee: self.create_pool_fee.to_account_info(),
-                observation_state: self.observation_state.to_account_info(),
-                token_program: self.token_program.to_account_info(),
-                token_0_program: self.token_0_program.to_account_info(),
-                token_1_program: self.token_1_program.to_account_info(),
-                associated_token_program: self.associated_token_program.to_account_info(),
-                system_program: self.system_program.to_account_info(),
-                rent: self.rent.to_account_info(),
-            };
-
-            let signer_seeds: &[&[&[u8]]] = &[&[
-                crate::TOKEN_SEED,  
-                &[bump_seed]
-            ]];
-
-            let cpi_ctx = CpiContext::new_with_signer(
-                self.cp_swap_program.to_account_info(),
-                cpi_accounts,
-                signer_seeds
-            );
-
-            cpi::initialize(cpi_ctx, amount_0, amount_1, open_time)?;
         }
 
-        msg!("Bought {} tokens for {} lamports", actual_token_amount, actual_amount);
-
-        if self.user_purchase.user_address == self.buyer.key() {
-            return Err(ErrorCode::UserAlreadyPurchased.into());
-        }   
+        let user_purchase = &mut self.user_purchase;
 
-        self.user_purchase.user_address = self.buyer.key();
-        self.user_purchase.token_amount = actual_token_amount as u64;
-        self.user_purchase.token_price = self.sale.price_per_token;
-        self.user_purchase.token_address = self.token_mint.key();
-        self.user_purchase.purchase_amount = actual_amount;
-        self.user_purchase.purchase_time = current_time;
-        self.user_purchase.is_claim = false;
+        user_purchase.user_address = self.buyer.key();
+        user_purchase.token_amount = user_purchase.token_amount
+            .checked_add(actual_token_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        user_purchase.price_numerator = self.sale.price_numerator;
+        user_purchase.price_denominator = self.sale.price_denominator;
+        user_purchase.token_address = self.token_mint.key();
+        user_purchase.purchase_amount = user_purchase.purchase_amount
+            .checked_add(received_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        user_purchase.purchase_time = current_time;
+        user_purchase.is_claim = false;
+        user_purchase.fee_amount = user_purchase.fee_amount
+            .checked_add(received_fee)
+            .ok_or(ErrorCode::Overflow)?;
+        user_purchase.refundable_fee_amount = user_purchase.refundable_fee_amount
+            .checked_add(refundable_fee)
+            .ok_or(ErrorCode::Overflow)?;
+        user_purchase.purchase_count = user_purchase.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
 
         Ok(())
     }
 
-    pub fn into_transfer_to_buyer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        CpiContext::new(
-            self.token_program.to_account_info(),
-            Transfer {
-                from: self.buyer_token_account.to_account_info(),
+    fn buyer_token_account(&self) -> Result<&InterfaceAccount<'info, TokenAccount>> {
+        Ok(self.buyer_token_account.as_deref().ok_or(ErrorCode::TokenAccountMismatch)?)
+    }
+
+    pub fn into_transfer_to_buyer_context(&self) -> Result<CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>> {
+        Ok(CpiContext::new(
+            self.buy_token_program.to_account_info(),
+            TransferChecked {
+                from: self.buyer_token_account()?.to_account_info(),
+                mint: self.buy_token_mint.to_account_info(),
                 to: self.sale_token_account.to_account_info(),
                 authority: self.buyer.to_account_info(),
             },
+        ))
+    }
+
+    // 可退还的手续费转入销售金库托管，否则直接转入平台金库
+    pub fn into_transfer_fee_context(&self, refundable: bool) -> Result<CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>> {
+        let to = if refundable {
+            self.sale_token_account.to_account_info()
+        } else {
+            self.treasury_token_account.to_account_info()
+        };
+
+        Ok(CpiContext::new(
+            self.buy_token_program.to_account_info(),
+            TransferChecked {
+                from: self.buyer_token_account()?.to_account_info(),
+                mint: self.buy_token_mint.to_account_info(),
+                to,
+                authority: self.buyer.to_account_info(),
+            },
+        ))
+    }
+
+    fn transfer_lamports(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.buyer.to_account_info(),
+                    to,
+                },
+            ),
+            amount
         )
     }
 } 
\ No newline at end of file
@@ -0,0 +1,105 @@
+use super::{SaleAccount, SaleStatus, PlatformConfig};
+use super::transfer_from_vault;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
+
+use super::error::ErrorCode;
+
+/// 所有者在销售结束前取消销售
+/// 托管的代币全部退还所有者，销售进入仅可退款的 Cancelled 状态
+#[derive(Accounts)]
+pub struct CancelSale<'info> {
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_SEED, token_mint.key().as_ref()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub sale: Account<'info, SaleAccount>,
+/// CHECK:` doc comment explaining why no checks through types are necessary.
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
+        bump,
+    )]
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    #[account(
+        constraint = token_mint.key() == sale.token_mint,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == token_mint.key()
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        constraint = sale_token_account.owner == pda_account.key(),
+        constraint = sale_token_account.mint == token_mint.key()
+    )]
+    pub sale_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CancelSale<'info> {
+    pub fn process(&mut self, bump_seed: u8) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, false)?;
+
+        // 只有尚未结算的销售可以取消
+        if !matches!(self.sale.status, SaleStatus::Pending | SaleStatus::Active) {
+            msg!("Sale can no longer be cancelled.");
+            return Err(ErrorCode::InvalidSaleStatus.into());
+        }
+
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time >= self.sale.end_time {
+            msg!("Sale ended.");
+            return Err(ErrorCode::SaleEnded.into());
+        }
+
+        // 已有购买时，由平台配置决定是否允许取消
+        if self.sale.sold_amount() > 0 && !self.config.cancel_with_purchases {
+            msg!("Sale has purchases and cannot be cancelled.");
+            return Err(ErrorCode::SaleHasPurchases.into());
+        }
+
+        // 托管的代币全部退还所有者，用户的购买代币留在合约中用于退款
+        let sale_balance = self.sale_token_account.amount;
+        if sale_balance > 0 {
+            transfer_from_vault(
+                &self.pda_account,
+                &self.sale_token_account,
+                &self.owner_token_account,
+                &self.token_mint,
+                &self.token_program,
+                self.sale.key(),
+                bump_seed,
+                sale_balance,
+            )?;
+        }
+
+        self.sale.status = SaleStatus::Cancelled;
+
+        msg!("Sale cancelled, returned {} tokens to owner", sale_balance);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use super::PlatformConfig;
+use anchor_lang::prelude::*;
+use super::error::ErrorCode;
+
+/// 管理员变更分两步完成：
+/// 1. 当前管理员提名新管理员（propose_admin）
+/// 2. 新管理员签名接受（accept_admin）
+///
+/// 避免误填地址导致管理权限永久丢失
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> ProposeAdmin<'info> {
+    pub fn process(&mut self, new_admin: Pubkey) -> Result<()> {
+        // 提名默认地址即为撤销提名
+        self.config.pending_admin = new_admin;
+
+        msg!("Proposed new admin {}", new_admin);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub new_admin: Signer<'info>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        // 检查是否存在待接受的管理员
+        if self.config.pending_admin == Pubkey::default() {
+            msg!("No pending admin.");
+            return Err(ErrorCode::NoPendingAdmin.into());
+        }
+
+        // 只有被提名的地址可以接受
+        if self.config.pending_admin != self.new_admin.key() {
+            msg!("Signer is not the pending admin.");
+            return Err(ErrorCode::Unauthorized.into());
+        }
+
+        self.config.admin = self.new_admin.key();
+        self.config.pending_admin = Pubkey::default();
+
+        msg!("Admin changed to {}", self.config.admin);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use super::{SaleAccount, SaleStatus, LpLock, LpPolicy};
+use super::PlatformConfig;
+use super::{has_transfer_fee, transfer_from_sol_vault, transfer_from_vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    TokenInterface, TokenAccount, Mint, CloseAccount, close_account,
+};
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    HarvestWithheldTokensToMint, harvest_withheld_tokens_to_mint,
+};
+
+use super::error::ErrorCode;
+
+/// 销售完全结算后，关闭托管账户及销售账户，租金退还所有者
+/// 托管账户中残留的余额（如他人转入的代币）一并转给所有者
+#[derive(Accounts)]
+pub struct CloseSale<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [crate::TOKEN_SEED, token_mint.key().as_ref()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub sale: Account<'info, SaleAccount>,
+/// CHECK:` doc comment explaining why no checks through types are necessary.
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
+        bump,
+    )]
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        constraint = token_mint.key() == sale.token_mint,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = buy_token_mint.key() == sale.buy_token_mint,
+        mint::token_program = buy_token_program,
+    )]
+    pub buy_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+    )]
+    pub sale_token_account: InterfaceAccount<'info, TokenAccount>, //销售代币托管账户
+
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
+    )]
+    pub contract_token_account: InterfaceAccount<'info, TokenAccount>, //购买代币托管账户（募集资金）
+
+    /// 所有者的代币账户，仅在销售代币托管账户有残留余额时需要
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == token_mint.key()
+    )]
+    pub owner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 所有者的购买代币账户，仅在购买代币托管账户有残留余额时需要
+    #[account(
+        mut,
+        constraint = owner_buy_token_account.owner == owner.key(),
+        constraint = owner_buy_token_account.mint == buy_token_mint.key()
+    )]
+    pub owner_buy_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: 原生SOL销售的lamports托管PDA，关闭时全部退还所有者
+    #[account(
+        mut,
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// LP锁仓账户，LP处理方式为锁定/线性释放时需要，且必须已全部提取
+    #[account(
+        seeds = [crate::LP_LOCK_SEED, sale.key().as_ref()],
+        bump = lp_lock.bump,
+        has_one = sale,
+    )]
+    pub lp_lock: Option<Account<'info, LpLock>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub buy_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseSale<'info> {
+    pub fn process(&mut self, bump_seed: u8, sol_vault_bump: u8) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, true)?;
+
+        // 只有已迁移、失败或取消的销售可以关闭
+        if !matches!(
+            self.sale.status,
+            SaleStatus::Migrated | SaleStatus::Failed | SaleStatus::Cancelled
+        ) {
+            msg!("Sale is not settled.");
+            return Err(ErrorCode::InvalidSaleStatus.into());
+        }
+
+        // 所有用户的购买记录都必须已领取或退款
+        if self.sale.open_positions > 0 {
+            msg!("{} purchases are not settled.", self.sale.open_positions);
+            return Err(ErrorCode::OpenPositions.into());
+        }
+
+        // 锁定的LP依赖销售账户提取，全部提取后才能关闭
+        if self.sale.status == SaleStatus::Migrated && self.sale.lp_policy != LpPolicy::Burn {
+            let lp_lock = self.lp_lock.as_ref().ok_or(ErrorCode::LpLocked)?;
+            if lp_lock.released_amount < lp_lock.total_amount {
+                msg!("LP tokens are still locked.");
+                return Err(ErrorCode::LpLocked.into());
+            }
+        }
+
+        let sale_key = self.sale.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_ACCOUNT, sale_key.as_ref(), &[bump_seed]]];
+
+        // 所有购买均已结算，托管账户的残留余额无人认领，转给所有者后再关闭
+        let vaults = [
+            (&self.sale_token_account, self.owner_token_account.as_ref(), &self.token_mint, &self.token_program),
+            (&self.contract_token_account, self.owner_buy_token_account.as_ref(), &self.buy_token_mint, &self.buy_token_program),
+        ];
+        for (vault, destination, mint, token_program) in vaults {
+            if vault.amount > 0 {
+                let destination = destination.ok_or(ErrorCode::TokenAccountMismatch)?;
+                transfer_from_vault(
+                    &self.pda_account,
+                    vault,
+                    destination,
+                    mint,
+                    token_program,
+                    sale_key,
+                    bump_seed,
+                    vault.amount,
+                )?;
+
+                msg!("Swept {} leftover tokens of {} to owner", vault.amount, mint.key());
+            }
+
+            // 转账手续费代币的托管账户中扣留的手续费会阻止关闭，先归集到Mint
+            if has_transfer_fee(mint)? {
+                harvest_withheld_tokens_to_mint(
+                    CpiContext::new(
+                        token_program.to_account_info(),
+                        HarvestWithheldTokensToMint {
+                            token_program_id: token_program.to_account_info(),
+                            mint: mint.to_account_info(),
+                        },
+                    ),
+                    vec![vault.to_account_info()],
+                )?;
+            }
+
+            close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: self.owner.to_account_info(),
+                    authority: self.pda_account.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+
+        // 原生SOL托管账户的免租余额及残留lamports，全部退还所有者
+        let sol_vault_lamports = self.sol_vault.lamports();
+        if sol_vault_lamports > 0 {
+            transfer_from_sol_vault(
+                &self.sol_vault,
+                &self.owner.to_account_info(),
+                &self.system_program,
+                sale_key,
+                sol_vault_bump,
+                sol_vault_lamports,
+            )?;
+        }
+
+        msg!("Closed sale {}", sale_key);
+
+        Ok(())
+    }
+}
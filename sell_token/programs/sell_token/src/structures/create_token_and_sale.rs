@@ -0,0 +1,226 @@
+use super::SaleAccount;
+use super::PlatformConfig;
+use super::SaleParams;
+use super::check_mint_extensions;
+use super::fund_rent_exempt;
+use super::init_sale_account::charge_listing_fee;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint, MintTo, mint_to};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::{
+    Metadata,
+    CreateMetadataAccountsV3,
+    create_metadata_accounts_v3,
+    mpl_token_metadata::types::DataV2,
+};
+
+/// 在合约内创建代币Mint及Metaplex元数据，并原子化地初始化销售
+/// 代币总量直接铸造到销售托管账户，铸币权限由合约PDA持有，迁移时撤销
+#[derive(Accounts)]
+#[instruction(params: SaleParams, nonce: u64, decimals: u8)]
+pub struct CreateTokenAndSale<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8+core::mem::size_of::<SaleAccount>(),
+        seeds = [crate::TOKEN_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub sale: Box<Account<'info, SaleAccount>>,
+
+/// CHECK:` doc comment explaining why no checks through types are necessary.
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
+        bump,
+    )]
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, PlatformConfig>>,
+
+    /// 新代币Mint，由所有者地址和序号派生
+    #[account(
+        init,
+        payer = owner,
+        seeds = [crate::TOKEN_MINT, owner.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+        mint::decimals = decimals,
+        mint::authority = pda_account,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mint::token_program = buy_token_program)]
+    pub buy_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = pda_account,
+        token::token_program = token_program,
+    )]
+    pub sale_token_account: Box<InterfaceAccount<'info, TokenAccount>>, //销售代币托管账户
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
+        token::mint = buy_token_mint,
+        token::authority = pda_account,
+        token::token_program = buy_token_program,
+    )]
+    pub contract_token_account: Box<InterfaceAccount<'info, TokenAccount>>, //购买代币托管账户（募集资金）
+
+    /// CHECK: 原生SOL销售的lamports托管PDA，仅在购买代币为WSOL时使用
+    #[account(
+        mut,
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// CHECK: 金库PDA，接收SOL计价的上架费并作为手续费代币账户的权限账户
+    #[account(
+        mut,
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = buy_token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = buy_token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 所有者的购买代币账户，仅在上架费以购买代币计价时需要
+    #[account(
+        mut,
+        constraint = owner_buy_token_account.owner == owner.key(),
+        constraint = owner_buy_token_account.mint == buy_token_mint.key()
+    )]
+    pub owner_buy_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex元数据账户，由元数据程序创建
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata_program.key().as_ref(), token_mint.key().as_ref()],
+        seeds::program = metadata_program.key(),
+        bump,
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    pub metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    /// 新代币的代币程序（SPL Token 或 Token-2022）
+    pub token_program: Interface<'info, TokenInterface>,
+    /// 购买代币的代币程序（SPL Token 或 Token-2022）
+    pub buy_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateTokenAndSale<'info> {
+    pub fn process(&mut self, params: SaleParams, total_supply: u64, name: String, symbol: String, uri: String, bump_seed: u8) -> Result<()> {
+        // 检查平台是否暂停
+        self.config.check_paused(false, false)?;
+
+        // 新代币由合约创建不带扩展，只需检查购买代币
+        check_mint_extensions(&self.buy_token_mint)?;
+
+        // 验证销售参数
+        params.validate(total_supply, self.config.max_sale_duration)?;
+
+        // 收取上架费
+        charge_listing_fee(
+            &self.config,
+            &self.owner,
+            self.owner_buy_token_account.as_ref(),
+            &self.buy_token_mint,
+            &self.treasury,
+            &self.treasury_token_account,
+            &self.buy_token_program,
+            &self.system_program,
+        )?;
+
+        let sale_key = self.sale.key();
+
+        let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_ACCOUNT, sale_key.as_ref(), &[bump_seed]]];
+
+        // 写入Metaplex元数据，更新权限归合约PDA且不可修改
+        create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                self.metadata_program.to_account_info(),
+                CreateMetadataAccountsV3 {
+                    metadata: self.metadata.to_account_info(),
+                    mint: self.token_mint.to_account_info(),
+                    mint_authority: self.pda_account.to_account_info(),
+                    payer: self.owner.to_account_info(),
+                    update_authority: self.pda_account.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    rent: self.rent.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            DataV2 {
+                name,
+                symbol,
+                uri,
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            false,
+            true,
+            None,
+        )?;
+
+        // 代币总量直接铸造到销售托管账户
+        mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.token_mint.to_account_info(),
+                    to: self.sale_token_account.to_account_info(),
+                    authority: self.pda_account.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            total_supply
+        )?;
+
+        // 铸造后重新加载，使 supply 与链上一致
+        self.token_mint.reload()?;
+
+        self.sale.initialize(
+            self.owner.key(),
+            self.token_mint.key(),
+            self.buy_token_mint.key(),
+            total_supply,
+            &params,
+        )?;
+
+        if self.sale.is_native() {
+            fund_rent_exempt(&self.owner, &self.sol_vault, &self.system_program)?;
+        }
+
+        msg!("Created token {} with supply {}", self.token_mint.key(), total_supply);
+
+        Ok(())
+    }
+}
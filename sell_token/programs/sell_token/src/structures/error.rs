@@ -67,6 +67,69 @@ pub enum ErrorCode {
 
     #[msg("MissingRequiredSignature.")]
     MissingRequiredSignature,
-    
+
+    #[msg("Invalid fee rate.")]
+    InvalidFeeRate,
+
+    #[msg("No pending admin.")]
+    NoPendingAdmin,
+
+    #[msg("Paused.")]
+    Paused,
+
+    #[msg("Sale not completed.")]
+    SaleNotCompleted,
+
+    #[msg("Liquidity already migrated.")]
+    AlreadyMigrated,
+
+    #[msg("Invalid LP policy.")]
+    InvalidLpPolicy,
+
+    #[msg("LP tokens are still locked.")]
+    LpLocked,
+
+    #[msg("Mint and freeze authority must be revoked or held by the program.")]
+    InvalidMintAuthority,
+
+    #[msg("Wallet cap exceeded.")]
+    WalletCapExceeded,
+
+    #[msg("Purchase is below the minimum.")]
+    BelowMinimumPurchase,
+
+    #[msg("Invalid purchase limit.")]
+    InvalidPurchaseLimit,
+
+    #[msg("Sale not started.")]
+    SaleNotStarted,
+
+    #[msg("Invalid start time.")]
+    InvalidStartTime,
+
+    #[msg("Sale duration too long.")]
+    SaleDurationTooLong,
+
+    #[msg("Invalid soft cap.")]
+    InvalidSoftCap,
+
+    #[msg("Invalid sale status.")]
+    InvalidSaleStatus,
+
+    #[msg("Sale has purchases.")]
+    SaleHasPurchases,
+
+    #[msg("Sale has unsettled purchases.")]
+    OpenPositions,
+
+    #[msg("Unsupported mint extension.")]
+    UnsupportedMintExtension,
+
+    #[msg("Invalid AMM config.")]
+    InvalidAmmConfig,
+
+    #[msg("Invalid migration timeout.")]
+    InvalidMigrationTimeout,
+
 }
 
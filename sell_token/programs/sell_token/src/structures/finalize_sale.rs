@@ -0,0 +1,64 @@
+use super::{SaleAccount, SaleStatus, PlatformConfig};
+use anchor_lang::prelude::*;
+
+use super::error::ErrorCode;
+
+/// 销售结束后结算状态，任何人都可以调用
+/// 售出数量达到软顶则进入 Succeeded，否则进入 Failed
+/// Succeeded 后超过平台配置的迁移期限仍未迁移，再次调用转为 Failed，买家可退款
+#[derive(Accounts)]
+pub struct FinalizeSale<'info> {
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_SEED, sale.token_mint.as_ref()],
+        bump
+    )]
+    pub sale: Account<'info, SaleAccount>,
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+}
+
+impl<'info> FinalizeSale<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        // 结算属于领取/退款路径
+        self.config.check_paused(self.sale.paused, true)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        if current_time < self.sale.end_time {
+            msg!("Sale has not ended yet.");
+            return Err(ErrorCode::SaleNotEnded.into());
+        }
+
+        // 迁移超时：流动性无法迁移时不能让募集资金永久锁定
+        if self.sale.status == SaleStatus::Succeeded {
+            let deadline = self.sale.end_time
+                .checked_add(self.config.migration_timeout)
+                .ok_or(ErrorCode::Overflow)?;
+            if current_time <= deadline {
+                msg!("Migration window has not expired.");
+                return Err(ErrorCode::InvalidSaleStatus.into());
+            }
+
+            self.sale.status = SaleStatus::Failed;
+
+            msg!("Migration timed out, sale marked as failed");
+
+            return Ok(());
+        }
+
+        if !matches!(self.sale.status, SaleStatus::Pending | SaleStatus::Active) {
+            msg!("Sale already finalized.");
+            return Err(ErrorCode::InvalidSaleStatus.into());
+        }
+
+        self.sale.status = self.sale.outcome();
+
+        msg!("Sale finalized, sold {} of {}", self.sale.sold_amount(), self.sale.sale_amount);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use super::{PlatformConfig, ConfigParams};
+use super::fund_rent_exempt;
+use anchor_lang::prelude::*;
+use super::error::ErrorCode;
+use crate::program::SellToken;
+
+/// 初始化平台全局配置
+/// 只有程序的升级权限持有者可以调用，且只能调用一次
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + core::mem::size_of::<PlatformConfig>(),
+        seeds = [crate::CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// 本程序，用于校验 program_data 账户
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, SellToken>,
+
+    /// 验证：调用者必须是程序的升级权限持有者
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// CHECK: 金库PDA，初始化时预存免租最低余额，使小额SOL手续费可以转入
+    #[account(
+        mut,
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitConfig<'info> {
+    pub fn process(&mut self, params: ConfigParams, bump: u8) -> Result<()> {
+        let config = &mut self.config;
+        config.apply(params)?;
+        config.admin = self.admin.key();
+        config.pending_admin = Pubkey::default();
+        config.bump = bump;
+
+        fund_rent_exempt(&self.admin, &self.treasury, &self.system_program)?;
+
+        msg!("Platform config initialized, admin {}", config.admin);
+
+        Ok(())
+    }
+}
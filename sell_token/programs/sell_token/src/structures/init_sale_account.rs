@@ -1,6 +1,11 @@
 use super::SaleAccount;
+use super::PlatformConfig;
+use super::SaleParams;
+use super::{check_mint_extensions, fund_rent_exempt, net_transfer_amount};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Transfer, Mint, transfer};
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, TransferChecked, Mint, SetAuthority, transfer_checked, set_authority};
+use anchor_spl::token_interface::spl_token_2022::instruction::AuthorityType;
 use anchor_spl::associated_token::AssociatedToken;
 use super::error::ErrorCode;
 
@@ -18,14 +23,26 @@ pub struct InitSaleAccount<'info> {
 /// CHECK:` doc comment explaining why no checks through types are necessary.
     #[account(
         mut,
-        seeds = [crate::TOKEN_SEED], 
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
         bump,
     )]
-    pub pda_account: AccountInfo<'info>, //合约pda账户
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
     
     
-    pub token_mint: Account<'info, Mint>,
-    pub buy_token_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        mint::token_program = token_program,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mint::token_program = buy_token_program)]
+    pub buy_token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -34,35 +51,86 @@ pub struct InitSaleAccount<'info> {
         constraint = owner_token_account.owner == owner.key(),
         constraint = owner_token_account.mint == token_mint.key()
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
     
     
     #[account(
         init,
         payer = owner,
-        associated_token::mint = token_mint,
-        associated_token::authority = pda_account
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = pda_account,
+        token::token_program = token_program,
+    )]
+    pub sale_token_account: Box<InterfaceAccount<'info, TokenAccount>>, //销售代币托管账户
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
+        token::mint = buy_token_mint,
+        token::authority = pda_account,
+        token::token_program = buy_token_program,
+    )]
+    pub contract_token_account: Box<InterfaceAccount<'info, TokenAccount>>, //购买代币托管账户（募集资金）
+
+    /// CHECK: 原生SOL销售的lamports托管PDA，仅在购买代币为WSOL时使用
+    #[account(
+        mut,
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// CHECK: 金库PDA，接收SOL计价的上架费并作为手续费代币账户的权限账户
+    #[account(
+        mut,
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = buy_token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = buy_token_program,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// 所有者的购买代币账户，仅在上架费以购买代币计价时需要
+    #[account(
+        mut,
+        constraint = owner_buy_token_account.owner == owner.key(),
+        constraint = owner_buy_token_account.mint == buy_token_mint.key()
     )]
-    pub sale_token_account: Account<'info, TokenAccount>,
+    pub owner_buy_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    /// 销售代币的代币程序（SPL Token 或 Token-2022）
+    pub token_program: Interface<'info, TokenInterface>,
+    /// 购买代币的代币程序（SPL Token 或 Token-2022）
+    pub buy_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> InitSaleAccount<'info> {
-    pub fn process(&mut self, sale_amount: u64, price_per_token: u64, end_time: i64) -> Result<()> {
-        // 验证销售数量
-        if sale_amount < self.token_mint.supply / 5 {
-            msg!("Sale amount is too low.");
-            return Err(ErrorCode::SaleAmountTooLow.into());
-        }
+    pub fn process(&mut self, params: SaleParams) -> Result<()> {
+        // 检查平台是否暂停
+        self.config.check_paused(false, false)?;
 
-        // 验证销售数量不能超过代币总量的80%
-        if sale_amount > self.token_mint.supply {
-            msg!("Sale amount is too high.");
-            return Err(ErrorCode::SaleAmountTooHigh.into());
-        }
+        // 拒绝无法安全托管的代币扩展
+        check_mint_extensions(&self.token_mint)?;
+        check_mint_extensions(&self.buy_token_mint)?;
+
+        // 代币带转账手续费时，托管账户实际到账少于代币总量
+        let deposited_amount = net_transfer_amount(&self.token_mint, self.token_mint.supply)?;
+
+        // 验证销售参数，销售数量不能超过实际托管的数量
+        params.validate(deposited_amount, self.config.max_sale_duration)?;
 
         // 检查代币余额是否足够
         let owner_balance = self.owner_token_account.amount;
@@ -71,49 +139,141 @@ impl<'info> InitSaleAccount<'info> {
             return Err(ErrorCode::InsufficientBalance.into());
         }
 
-        // 验证价格
-        if price_per_token == 0 {
-            msg!("Price per token cannot be zero.");
-            return Err(ErrorCode::InvalidPrice.into());
-        }
+        // 铸币及冻结权限必须已撤销或转移给合约，迁移时统一撤销
+        self.take_mint_authorities()?;
 
-        // 验证结束时间
-        let current_time = Clock::get()?.unix_timestamp;
-        if end_time <= current_time {
-            msg!("End time must be in the future.");
-            return Err(ErrorCode::InvalidEndTime.into());
-        }
+        // 收取上架费
+        charge_listing_fee(
+            &self.config,
+            &self.owner,
+            self.owner_buy_token_account.as_ref(),
+            &self.buy_token_mint,
+            &self.treasury,
+            &self.treasury_token_account,
+            &self.buy_token_program,
+            &self.system_program,
+        )?;
 
         msg!("self.token_mint.supply {}",self.token_mint.supply);
         // 划转token
-        transfer(
+        transfer_checked(
             self.into_transfer_to_vault_context(),
             
             //代币总量转入
-            self.token_mint.supply
+            self.token_mint.supply,
+            self.token_mint.decimals
         )?;
 
-        let sale = &mut self.sale;
-        sale.owner = self.owner.key();
-        sale.token_mint = self.token_mint.key();
-        sale.sale_amount = sale_amount;
-        sale.remaining_amount = sale_amount;
-        sale.price_per_token = price_per_token;
-        sale.end_time = end_time;
-        sale.is_active = true;
-        sale.buy_token_mint = self.buy_token_mint.key();
+        self.sale.initialize(
+            self.owner.key(),
+            self.token_mint.key(),
+            self.buy_token_mint.key(),
+            deposited_amount,
+            &params,
+        )?;
+
+        if self.sale.is_native() {
+            fund_rent_exempt(&self.owner, &self.sol_vault, &self.system_program)?;
+        }
 
         Ok(())
     }
 
-    pub fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+    fn take_mint_authorities(&self) -> Result<()> {
+        let authorities = [
+            (self.token_mint.mint_authority, AuthorityType::MintTokens),
+            (self.token_mint.freeze_authority, AuthorityType::FreezeAccount),
+        ];
+
+        for (authority, authority_type) in authorities {
+            match Option::<Pubkey>::from(authority) {
+                None => {}
+                Some(key) if key == self.pda_account.key() => {}
+                Some(key) if key == self.owner.key() => {
+                    // 所有者持有的权限转移给合约PDA
+                    set_authority(
+                        CpiContext::new(
+                            self.token_program.to_account_info(),
+                            SetAuthority {
+                                current_authority: self.owner.to_account_info(),
+                                account_or_mint: self.token_mint.to_account_info(),
+                            },
+                        ),
+                        authority_type,
+                        Some(self.pda_account.key()),
+                    )?;
+                }
+                Some(_) => {
+                    msg!("Mint or freeze authority is held by a third party.");
+                    return Err(ErrorCode::InvalidMintAuthority.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn into_transfer_to_vault_context(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
         CpiContext::new(
             self.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: self.owner_token_account.to_account_info(),
+                mint: self.token_mint.to_account_info(),
                 to: self.sale_token_account.to_account_info(),
                 authority: self.owner.to_account_info(),
             },
         )
     }
 }
+
+/// 收取上架费，init_sale_account 与 create_token_and_sale 共用
+#[allow(clippy::too_many_arguments)]
+pub fn charge_listing_fee<'info>(
+    config: &PlatformConfig,
+    owner: &Signer<'info>,
+    owner_buy_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    buy_token_mint: &InterfaceAccount<'info, Mint>,
+    treasury: &AccountInfo<'info>,
+    treasury_token_account: &InterfaceAccount<'info, TokenAccount>,
+    buy_token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let listing_fee = config.listing_fee;
+    if listing_fee == 0 {
+        return Ok(());
+    }
+
+    if config.listing_fee_in_quote {
+        let owner_buy_token_account = owner_buy_token_account
+            .ok_or(ErrorCode::TokenAccountMismatch)?;
+
+        transfer_checked(
+            CpiContext::new(
+                buy_token_program.to_account_info(),
+                TransferChecked {
+                    from: owner_buy_token_account.to_account_info(),
+                    mint: buy_token_mint.to_account_info(),
+                    to: treasury_token_account.to_account_info(),
+                    authority: owner.to_account_info(),
+                },
+            ),
+            listing_fee,
+            buy_token_mint.decimals
+        )?;
+    } else {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: owner.to_account_info(),
+                    to: treasury.to_account_info(),
+                },
+            ),
+            listing_fee
+        )?;
+    }
+
+    msg!("Charged listing fee {}", listing_fee);
+
+    Ok(())
+}
@@ -0,0 +1,669 @@
+use super::{SaleAccount, SaleStatus};
+use super::PlatformConfig;
+use super::MigrationFeeCharged;
+use super::{LpLock, LpPolicy};
+use super::{sol_vault_balance, transfer_from_sol_vault, transfer_from_vault};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use anchor_spl::{
+    associated_token::{self, AssociatedToken, get_associated_token_address},
+    token::{self, Token},
+    token_interface::{
+        Mint, TokenAccount, TokenInterface, Burn, SetAuthority, SyncNative,
+        burn, set_authority, sync_native,
+    },
+    token_interface::spl_token_2022::instruction::AuthorityType,
+};
+
+use raydium_cp_swap::{
+    cpi,
+    program::RaydiumCpSwap,
+    states::{AmmConfig, ObservationState, PoolState, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED},
+};
+
+use super::error::ErrorCode;
+
+/// 销售全部售出后，将流动性迁移至Raydium
+/// 任何人（或keeper）都可以调用
+#[derive(Accounts)]
+pub struct MigrateLiquidity<'info> {
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_SEED, token_mint.key().as_ref()],
+        bump
+    )]
+    pub sale: Box<Account<'info, SaleAccount>>,
+/// CHECK:` doc comment explaining why no checks through types are necessary.
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
+        bump,
+    )]
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        mut,
+        constraint = token_mint.key() == sale.token_mint,
+        mint::token_program = sale_token_program,
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = buy_token_mint.key() == sale.buy_token_mint,
+        mint::token_program = buy_token_program,
+    )]
+    pub buy_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// 调用者，支付交易费用
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// 合约持有的销售代币账户
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        constraint = sale_token_account.owner == pda_account.key(),
+        constraint = sale_token_account.mint == token_mint.key()
+    )]
+    pub sale_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// 合约持有的购买代币账户（募集资金）
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
+        token::mint = buy_token_mint,
+        token::authority = pda_account,
+    )]
+    pub contract_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: 原生SOL销售的lamports托管PDA
+    #[account(
+        mut,
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// CHECK: 金库PDA，接收原生SOL迁移手续费并作为手续费代币账户的权限账户
+    #[account(
+        mut,
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = buy_token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = buy_token_program,
+    )]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // Raydium accounts
+    pub cp_swap_program: Program<'info, RaydiumCpSwap>,
+    
+    /// 建池使用的手续费档位由平台配置指定，调用者不能自选
+    #[account(address = config.amm_config @ ErrorCode::InvalidAmmConfig)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// CHECK: Authority is a PDA owned by Raydium
+    #[account(
+        seeds = [
+            raydium_cp_swap::AUTH_SEED.as_bytes(),
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// CHECK: Pool state is initialized by Raydium
+    /// Raydium要求 token_0_mint < token_1_mint，池地址按排序后的mint推导
+    #[account(
+        mut,
+        seeds = [
+            POOL_SEED.as_bytes(),
+            amm_config.key().as_ref(),
+            std::cmp::min(token_mint.key(), buy_token_mint.key()).as_ref(),
+            std::cmp::max(token_mint.key(), buy_token_mint.key()).as_ref(),
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub pool_state: UncheckedAccount<'info>,
+
+    /// CHECK: LP mint is initialized by Raydium
+    #[account(
+        mut,
+        seeds = [
+            POOL_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Creator LP token account is initialized by Raydium
+    /// 必须是合约PDA持有的LP代币关联账户，迁移后其中的LP将被销毁
+    #[account(
+        mut,
+        address = get_associated_token_address(&pda_account.key(), &lp_mint.key()),
+    )]
+    pub creator_lp_token: UncheckedAccount<'info>,
+
+    /// LP锁仓账户，仅在LP处理方式为锁定/线性释放时需要
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + core::mem::size_of::<LpLock>(),
+        seeds = [crate::LP_LOCK_SEED, sale.key().as_ref()],
+        bump
+    )]
+    pub lp_lock: Option<Box<Account<'info, LpLock>>>,
+
+    /// CHECK: LP锁仓账户的LP关联代币账户，在CPI之后创建并校验地址
+    #[account(mut)]
+    pub lp_lock_token_account: Option<UncheckedAccount<'info>>,
+
+    /// 所有者的代币账户，仅在未售出代币退还所有者时需要
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == sale.owner,
+        constraint = owner_token_account.mint == token_mint.key()
+    )]
+    pub owner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Token vaults are initialized by Raydium
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            token_mint.key().as_ref()
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub sale_token_vault: UncheckedAccount<'info>,
+
+     /// CHECK: Token vaults are initialized by Raydium
+    #[account(
+        mut,
+        seeds = [
+            POOL_VAULT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            buy_token_mint.key().as_ref()
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub buy_token_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address= raydium_cp_swap::create_pool_fee_reveiver::ID,
+    )]
+    pub create_pool_fee: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Observation state is initialized by Raydium
+    #[account(
+        mut,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub observation_state: UncheckedAccount<'info>,
+
+    /// LP Mint使用的SPL Token程序
+    pub token_program: Program<'info, Token>,
+    /// 销售代币使用的程序，Spl token program or token program 2022
+    pub sale_token_program: Interface<'info, TokenInterface>,
+    /// 购买代币使用的程序，Spl token program or token program 2022
+    pub buy_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> MigrateLiquidity<'info> {
+    pub fn process(&mut self, bump_seed: u8, lp_lock_bump: Option<u8>, sol_vault_bump: u8) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, false)?;
+
+        // 只有成功的销售才能迁移
+        match self.sale.status {
+            SaleStatus::Succeeded => {}
+            SaleStatus::Migrated => {
+                msg!("Liquidity already migrated.");
+                return Err(ErrorCode::AlreadyMigrated.into());
+            }
+            _ => {
+                msg!("Sale is not completed.");
+                return Err(ErrorCode::SaleNotCompleted.into());
+            }
+        }
+
+        let sale_key = self.sale.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            crate::TOKEN_ACCOUNT,
+            sale_key.as_ref(),
+            &[bump_seed]
+        ]];
+
+        // 收取迁移手续费并将托管的mint手续费划入金库，均从募集的购买代币中划转
+        self.charge_migration_fee(bump_seed, sol_vault_bump)?;
+
+        // 原生SOL销售募集的是lamports，在此处才包装为WSOL用于建池
+        if self.sale.is_native() {
+            self.wrap_native(sol_vault_bump)?;
+        }
+
+        // Add liquidity to Raydium
+        //扣除手续费后的募集资金全部投入，按销售价格换算配对的代币数量，保证开盘价与销售价一致
+        //流动性部分（实际托管总量减去sale_amount）不足时全部投入，开盘价高于销售价
+        let amount_1 = self.contract_token_account.amount;
+
+        let liquidity_amount = self.sale.deposited_amount
+            .checked_sub(self.sale.sale_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let amount_0 = self.sale.tokens_for(amount_1)?.min(liquidity_amount);
+
+        // 未售出的代币及多余的流动性部分，按配置销毁或退还所有者
+        let unsold_amount = liquidity_amount
+            .checked_sub(amount_0)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_add(self.sale.remaining_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        self.settle_unsold(unsold_amount, bump_seed, signer_seeds)?;
+
+        // 建池的creator为合约PDA，由其支付Raydium创建各账户的租金及建池费，先由调用者垫付
+        self.fund_pool_creation()?;
+
+        // Raydium要求 token_0_mint < token_1_mint，按mint地址排序后传入对应的账户与数量
+        let sale_is_token_0 = self.token_mint.key() < self.buy_token_mint.key();
+        let order = |sale: AccountInfo<'info>, buy: AccountInfo<'info>| {
+            if sale_is_token_0 { (sale, buy) } else { (buy, sale) }
+        };
+        let (token_0_mint, token_1_mint) = order(
+            self.token_mint.to_account_info(),
+            self.buy_token_mint.to_account_info(),
+        );
+        let (creator_token_0, creator_token_1) = order(
+            self.sale_token_account.to_account_info(),
+            self.contract_token_account.to_account_info(),
+        );
+        let (token_0_vault, token_1_vault) = order(
+            self.sale_token_vault.to_account_info(),
+            self.buy_token_vault.to_account_info(),
+        );
+        let (token_0_program, token_1_program) = order(
+            self.sale_token_program.to_account_info(),
+            self.buy_token_program.to_account_info(),
+        );
+        let (init_amount_0, init_amount_1) = if sale_is_token_0 {
+            (amount_0, amount_1)
+        } else {
+            (amount_1, amount_0)
+        };
+
+        let cpi_accounts = cpi::accounts::Initialize {
+            creator: self.pda_account.to_account_info(),
+            amm_config: self.amm_config.to_account_info(),
+            authority: self.authority.to_account_info(),
+            pool_state: self.pool_state.to_account_info(),
+            token_0_mint,
+            token_1_mint,
+            lp_mint: self.lp_mint.to_account_info(),
+            creator_token_0,
+            creator_token_1,
+            creator_lp_token: self.creator_lp_token.to_account_info(),
+            token_0_vault,
+            token_1_vault,
+            create_pool_fee: self.create_pool_fee.to_account_info(),
+            observation_state: self.observation_state.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_0_program,
+            token_1_program,
+            associated_token_program: self.associated_token_program.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            rent: self.rent.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.cp_swap_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds
+        );
+
+        // 开盘时间取链上当前时间，迁移后立即可交易，不由调用者指定
+        let open_time = Clock::get()?.unix_timestamp as u64;
+        cpi::initialize(cpi_ctx, init_amount_0, init_amount_1, open_time)?;
+
+        // 预估租金的余量退还调用者
+        self.refund_pool_creation(signer_seeds)?;
+
+        // 按销售配置处理合约PDA持有的LP代币
+        match self.sale.lp_policy {
+            LpPolicy::Burn => {
+                // 全部销毁，防止撤池
+                let lp_burned = self.burn_lp(signer_seeds)?;
+                self.sale.lp_burned = lp_burned;
+
+                msg!("Burned {} LP tokens of pool {}", lp_burned, self.pool_state.key());
+            }
+            policy => {
+                let lp_locked = self.lock_lp(signer_seeds, policy, lp_lock_bump)?;
+
+                msg!("Locked {} LP tokens of pool {}", lp_locked, self.pool_state.key());
+            }
+        }
+
+        // 撤销铸币及冻结权限，保证代币总量固定
+        self.revoke_mint_authorities(signer_seeds)?;
+
+        self.sale.status = SaleStatus::Migrated;
+        self.sale.pool_state = self.pool_state.key();
+
+        msg!("Migrated {} tokens and {} buy tokens to Raydium", amount_0, amount_1);
+
+        Ok(())
+    }
+
+    fn fund_pool_creation(&self) -> Result<()> {
+        let rent = Rent::get()?;
+        // 池状态、观察账户、LP Mint、两个池金库及creator的LP账户
+        let amount = [
+            self.amm_config.create_pool_fee,
+            rent.minimum_balance(PoolState::LEN),
+            rent.minimum_balance(ObservationState::LEN),
+            rent.minimum_balance(crate::POOL_ACCOUNT_SPACE)
+                .checked_mul(4)
+                .ok_or(ErrorCode::Overflow)?,
+        ]
+        .iter()
+        .try_fold(0u64, |acc, x| acc.checked_add(*x))
+        .ok_or(ErrorCode::Overflow)?;
+
+        system_program::transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.payer.to_account_info(),
+                    to: self.pda_account.to_account_info(),
+                },
+            ),
+            amount
+        )
+    }
+
+    fn refund_pool_creation(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let remaining = self.pda_account.lamports();
+        if remaining == 0 {
+            return Ok(());
+        }
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.pda_account.to_account_info(),
+                    to: self.payer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            remaining
+        )
+    }
+
+    fn charge_migration_fee(&mut self, bump_seed: u8, sol_vault_bump: u8) -> Result<()> {
+        let migration_fee = self.config.migration_fee;
+        let escrowed_fees = self.sale.escrowed_fees;
+        let amount = migration_fee
+            .checked_add(escrowed_fees)
+            .ok_or(ErrorCode::Overflow)?;
+        if amount == 0 {
+            return Ok(());
+        }
+
+        if self.sale.is_native() {
+            if sol_vault_balance(&self.sol_vault)? <= amount {
+                msg!("Raised amount does not cover the migration fee.");
+                return Err(ErrorCode::InsufficientBalance.into());
+            }
+
+            transfer_from_sol_vault(
+                &self.sol_vault,
+                &self.treasury,
+                &self.system_program,
+                self.sale.key(),
+                sol_vault_bump,
+                amount,
+            )?;
+        } else {
+            if self.contract_token_account.amount <= amount {
+                msg!("Raised amount does not cover the migration fee.");
+                return Err(ErrorCode::InsufficientBalance.into());
+            }
+
+            transfer_from_vault(
+                &self.pda_account,
+                &self.contract_token_account,
+                &self.treasury_token_account,
+                &self.buy_token_mint,
+                &self.buy_token_program,
+                self.sale.key(),
+                bump_seed,
+                amount,
+            )?;
+
+            self.contract_token_account.reload()?;
+        }
+
+        // 销售成功后托管的mint手续费不再可退
+        self.sale.escrowed_fees = 0;
+        msg!("Released {} escrowed mint fees", escrowed_fees);
+
+        if migration_fee > 0 {
+            msg!("Charged migration fee {}", migration_fee);
+            emit!(MigrationFeeCharged {
+                sale: self.sale.key(),
+                fee_mint: self.buy_token_mint.key(),
+                amount: migration_fee,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn wrap_native(&mut self, sol_vault_bump: u8) -> Result<()> {
+        let raised = sol_vault_balance(&self.sol_vault)?;
+
+        transfer_from_sol_vault(
+            &self.sol_vault,
+            &self.contract_token_account.to_account_info(),
+            &self.system_program,
+            self.sale.key(),
+            sol_vault_bump,
+            raised,
+        )?;
+
+        sync_native(CpiContext::new(
+            self.buy_token_program.to_account_info(),
+            SyncNative {
+                account: self.contract_token_account.to_account_info(),
+            },
+        ))?;
+
+        self.contract_token_account.reload()?;
+
+        msg!("Wrapped {} lamports into WSOL", raised);
+
+        Ok(())
+    }
+
+    fn settle_unsold(&self, unsold_amount: u64, bump_seed: u8, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        if unsold_amount == 0 {
+            return Ok(());
+        }
+
+        if self.sale.burn_unsold {
+            burn(
+                CpiContext::new_with_signer(
+                    self.sale_token_program.to_account_info(),
+                    Burn {
+                        mint: self.token_mint.to_account_info(),
+                        from: self.sale_token_account.to_account_info(),
+                        authority: self.pda_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                unsold_amount
+            )?;
+
+            msg!("Burned {} unsold tokens", unsold_amount);
+        } else {
+            let owner_token_account = self.owner_token_account
+                .as_ref()
+                .ok_or(ErrorCode::TokenAccountMismatch)?;
+
+            transfer_from_vault(
+                &self.pda_account,
+                &self.sale_token_account,
+                owner_token_account,
+                &self.token_mint,
+                &self.sale_token_program,
+                self.sale.key(),
+                bump_seed,
+                unsold_amount,
+            )?;
+
+            msg!("Returned {} unsold tokens to owner", unsold_amount);
+        }
+
+        Ok(())
+    }
+
+    fn revoke_mint_authorities(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+        let authorities = [
+            (self.token_mint.mint_authority, AuthorityType::MintTokens),
+            (self.token_mint.freeze_authority, AuthorityType::FreezeAccount),
+        ];
+
+        for (authority, authority_type) in authorities {
+            if authority.is_none() {
+                continue;
+            }
+
+            set_authority(
+                CpiContext::new_with_signer(
+                    self.sale_token_program.to_account_info(),
+                    SetAuthority {
+                        current_authority: self.pda_account.to_account_info(),
+                        account_or_mint: self.token_mint.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                authority_type,
+                None,
+            )?;
+        }
+
+        msg!("Revoked mint and freeze authority of {}", self.token_mint.key());
+
+        Ok(())
+    }
+
+    // LP账户由Raydium在CPI中创建，需要重新读取余额
+    fn lp_balance(&self) -> Result<u64> {
+        let data = self.creator_lp_token.try_borrow_data()?;
+        Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+    }
+
+    fn burn_lp(&self, signer_seeds: &[&[&[u8]]]) -> Result<u64> {
+        let lp_amount = self.lp_balance()?;
+
+        if lp_amount > 0 {
+            // Raydium的LP Mint固定使用SPL Token程序
+            token::burn(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    token::Burn {
+                        mint: self.lp_mint.to_account_info(),
+                        from: self.creator_lp_token.to_account_info(),
+                        authority: self.pda_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                lp_amount
+            )?;
+        }
+
+        Ok(lp_amount)
+    }
+
+    fn lock_lp(&mut self, signer_seeds: &[&[&[u8]]], policy: LpPolicy, lp_lock_bump: Option<u8>) -> Result<u64> {
+        let lp_amount = self.lp_balance()?;
+
+        let lp_lock = self.lp_lock.as_mut().ok_or(ErrorCode::InvalidLpPolicy)?;
+        let lp_lock_token_account = self.lp_lock_token_account.as_ref().ok_or(ErrorCode::TokenAccountMismatch)?;
+
+        // 锁仓代币账户必须是LP锁仓账户的关联账户
+        if lp_lock_token_account.key() != get_associated_token_address(&lp_lock.key(), &self.lp_mint.key()) {
+            msg!("LP lock token account mismatch.");
+            return Err(ErrorCode::TokenAccountMismatch.into());
+        }
+
+        // LP Mint在CPI中才创建，因此锁仓代币账户在此处创建
+        associated_token::create(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: self.payer.to_account_info(),
+                associated_token: lp_lock_token_account.to_account_info(),
+                authority: lp_lock.to_account_info(),
+                mint: self.lp_mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))?;
+
+        if lp_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    token::Transfer {
+                        from: self.creator_lp_token.to_account_info(),
+                        to: lp_lock_token_account.to_account_info(),
+                        authority: self.pda_account.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                lp_amount
+            )?;
+        }
+
+        lp_lock.sale = self.sale.key();
+        lp_lock.owner = self.sale.owner;
+        lp_lock.lp_mint = self.lp_mint.key();
+        lp_lock.policy = policy;
+        lp_lock.total_amount = lp_amount;
+        lp_lock.released_amount = 0;
+        lp_lock.bump = lp_lock_bump.ok_or(ErrorCode::InvalidLpPolicy)?;
+
+        Ok(lp_amount)
+    }
+}
@@ -1,12 +1,30 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
+use anchor_spl::token_interface::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions, transfer_fee::TransferFeeConfig},
+};
 
 pub mod error;
 pub mod init_sale_account;
 pub mod buy_token;
 pub mod withdraw_tokens;
 pub mod withdraw_sale_tokens;
+pub mod init_config;
+pub mod update_config;
+pub mod change_admin;
+pub mod withdraw_treasury;
+pub mod set_pause;
+pub mod migrate_liquidity;
+pub mod release_lp;
+pub mod create_token_and_sale;
+pub mod finalize_sale;
+pub mod cancel_sale;
+pub mod close_sale;
 
-// pub  mod  change_admin;
+use error::ErrorCode;
 
 
 #[account]
@@ -15,10 +33,516 @@ pub struct SaleAccount {
     pub token_mint: Pubkey, // 代币Mint
     pub sale_amount: u64, // 销售数量
     pub remaining_amount: u64, // 剩余数量
-    pub price_per_token: u64, // 每代币价格
+    pub price_numerator: u64, // 价格分子：price_denominator 个代币最小单位对应的购买代币最小单位数量
+    pub price_denominator: u64, // 价格分母
     pub buy_token_mint: Pubkey, // 购买代币Mint
+    pub start_time: i64, // 开始时间
     pub end_time: i64, // 结束时间
-    pub is_active: bool, // 是否活跃
+    pub status: SaleStatus, // 销售状态
+    pub paused: bool, // 是否暂停
+    pub pool_state: Pubkey, // Raydium池地址
+    pub lp_burned: u64, // 已销毁的LP数量
+    pub lp_policy: LpPolicy, // 迁移后LP的处理方式
+    pub max_per_wallet: u64, // 每个地址累计最多可购买的代币数量，0表示不限制
+    pub min_per_purchase: u64, // 单次最少购买的代币数量
+    pub soft_cap: u64, // 软顶：结束时售出数量达到该值即视为成功
+    pub burn_unsold: bool, // 成功后未售出的代币 true: 销毁; false: 退还所有者
+    pub total_claimed: u64, // 用户已领取的代币总量
+    pub total_refunded: u64, // 已退还用户的购买代币总量
+    pub open_positions: u32, // 尚未结算的用户购买记录数量
+    pub deposited_amount: u64, // 托管账户实际收到的代币总量（扣除转账手续费后）
+    pub escrowed_fees: u64, // 可退还的mint手续费，结算前托管在销售的购买代币金库中，迁移时才划入平台金库
+}
+
+impl SaleAccount {
+    pub fn initialize(&mut self, owner: Pubkey, token_mint: Pubkey, buy_token_mint: Pubkey, deposited_amount: u64, params: &SaleParams) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+
+        self.owner = owner;
+        self.token_mint = token_mint;
+        self.sale_amount = params.sale_amount;
+        self.remaining_amount = params.sale_amount;
+        self.price_numerator = params.price_numerator;
+        self.price_denominator = params.price_denominator;
+        self.start_time = params.start_time;
+        self.end_time = params.end_time;
+        self.status = if current_time < params.start_time { SaleStatus::Pending } else { SaleStatus::Active };
+        self.paused = false;
+        self.lp_policy = params.lp_policy;
+        self.max_per_wallet = params.max_per_wallet;
+        self.min_per_purchase = params.min_per_purchase;
+        // 未设置软顶时需全部售出
+        self.soft_cap = if params.soft_cap == 0 { params.sale_amount } else { params.soft_cap };
+        self.burn_unsold = params.burn_unsold;
+        self.buy_token_mint = buy_token_mint;
+        self.total_claimed = 0;
+        self.total_refunded = 0;
+        self.open_positions = 0;
+        self.deposited_amount = deposited_amount;
+        self.escrowed_fees = 0;
+
+        Ok(())
+    }
+
+    //到达开始时间后由 Pending 进入 Active
+    pub fn refresh_status(&mut self, current_time: i64) {
+        if self.status == SaleStatus::Pending && current_time >= self.start_time {
+            self.status = SaleStatus::Active;
+        }
+    }
+
+    //按价格计算预算可换取的代币数量（代币最小单位，向下取整）
+    pub fn tokens_for(&self, budget: u64) -> Result<u64> {
+        let tokens = (budget as u128)
+            .checked_mul(self.price_denominator as u128)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(self.price_numerator as u128)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // 超出 u64 时按上限处理，调用方会再与剩余数量取较小值
+        Ok(u64::try_from(tokens).unwrap_or(u64::MAX))
+    }
+
+    //按价格计算购买指定数量代币需支付的金额（购买代币最小单位，向上取整）
+    pub fn cost_of(&self, token_amount: u64) -> Result<u64> {
+        let cost = (token_amount as u128)
+            .checked_mul(self.price_numerator as u128)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_add(self.price_denominator as u128 - 1)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(self.price_denominator as u128)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+
+    //已售出数量
+    pub fn sold_amount(&self) -> u64 {
+        self.sale_amount.saturating_sub(self.remaining_amount)
+    }
+
+    // 购买代币为WSOL时按原生SOL销售，募集的lamports存放在 sol_vault 中，迁移时才包装为WSOL
+    pub fn is_native(&self) -> bool {
+        self.buy_token_mint == native_mint::ID
+    }
+
+    //结束时的销售结果：售出数量达到软顶即成功
+    pub fn outcome(&self) -> SaleStatus {
+        if self.sold_amount() >= self.soft_cap {
+            SaleStatus::Succeeded
+        } else {
+            SaleStatus::Failed
+        }
+    }
+
+    //检查购买数量限制，purchased 为该地址此前累计购买的代币数量
+    pub fn check_purchase_limits(&self, token_amount: u64, purchased: u64) -> Result<()> {
+        // 剩余数量不足最小购买量时，允许买完剩余部分
+        if token_amount < self.min_per_purchase && token_amount < self.remaining_amount {
+            msg!("Purchase is below the minimum.");
+            return Err(ErrorCode::BelowMinimumPurchase.into());
+        }
+
+        if self.max_per_wallet > 0 {
+            let total = purchased.checked_add(token_amount).ok_or(ErrorCode::Overflow)?;
+            if total > self.max_per_wallet {
+                msg!("Wallet cap exceeded.");
+                return Err(ErrorCode::WalletCapExceeded.into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//销售生命周期
+// Pending -> Active -> Succeeded -> Migrated
+//                   -> Failed
+// Pending/Active -> Cancelled（所有者取消）
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SaleStatus {
+    Pending, // 已创建，未到开始时间
+    Active, // 销售中
+    Succeeded, // 全部售出或结束时达到软顶，等待迁移流动性
+    Migrated, // 流动性已迁移至Raydium，可领取代币
+    Failed, // 结束时未达到软顶，可退款
+    Cancelled, // 已取消，仅可退款
+}
+
+//创建销售的参数，init_sale_account 与 create_token_and_sale 共用
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SaleParams {
+    pub sale_amount: u64, // 销售数量
+    pub price_numerator: u64, // 价格分子：price_denominator 个代币最小单位对应的购买代币最小单位数量
+    pub price_denominator: u64, // 价格分母
+    pub start_time: i64, // 开始时间
+    pub end_time: i64, // 结束时间
+    pub lp_policy: LpPolicy, // 迁移后LP的处理方式
+    pub max_per_wallet: u64, // 每个地址累计最多可购买的代币数量，0表示不限制
+    pub min_per_purchase: u64, // 单次最少购买的代币数量
+    pub soft_cap: u64, // 软顶，0表示需全部售出
+    pub burn_unsold: bool, // 成功后未售出的代币 true: 销毁; false: 退还所有者
+}
+
+impl SaleParams {
+    pub fn validate(&self, supply: u64, max_sale_duration: i64) -> Result<()> {
+        // 验证销售数量
+        if self.sale_amount < supply / 5 {
+            msg!("Sale amount is too low.");
+            return Err(ErrorCode::SaleAmountTooLow.into());
+        }
+
+        // 销售数量必须小于代币总量，剩余部分用于迁移时的流动性
+        if self.sale_amount >= supply {
+            msg!("Sale amount is too high.");
+            return Err(ErrorCode::SaleAmountTooHigh.into());
+        }
+
+        // 验证价格
+        if self.price_numerator == 0 || self.price_denominator == 0 {
+            msg!("Price numerator and denominator cannot be zero.");
+            return Err(ErrorCode::InvalidPrice.into());
+        }
+
+        // 验证结束时间
+        let current_time = Clock::get()?.unix_timestamp;
+        if self.end_time <= current_time {
+            msg!("End time must be in the future.");
+            return Err(ErrorCode::InvalidEndTime.into());
+        }
+
+        // 验证开始时间及销售时长
+        if self.start_time >= self.end_time {
+            msg!("Start time must be before end time.");
+            return Err(ErrorCode::InvalidStartTime.into());
+        }
+
+        let duration = self.end_time
+            .checked_sub(std::cmp::max(self.start_time, current_time))
+            .ok_or(ErrorCode::Overflow)?;
+        if max_sale_duration > 0 && duration > max_sale_duration {
+            msg!("Sale duration exceeds the maximum.");
+            return Err(ErrorCode::SaleDurationTooLong.into());
+        }
+
+        // 验证软顶
+        if self.soft_cap > self.sale_amount {
+            msg!("Soft cap exceeds the sale amount.");
+            return Err(ErrorCode::InvalidSoftCap.into());
+        }
+
+        // 验证购买数量限制
+        if self.max_per_wallet > 0 && self.min_per_purchase > self.max_per_wallet {
+            msg!("Minimum purchase exceeds the wallet cap.");
+            return Err(ErrorCode::InvalidPurchaseLimit.into());
+        }
+
+        // 验证LP处理方式
+        self.lp_policy.validate(self.end_time)
+    }
+}
+
+//迁移后LP代币的处理方式
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum LpPolicy {
+    Burn, // 全部销毁
+    LockUntil { unlock_time: i64 }, // 锁定至指定时间后一次性释放
+    Vest { start_time: i64, end_time: i64 }, // 在时间区间内线性释放
+}
+
+impl LpPolicy {
+    pub fn validate(&self, sale_end_time: i64) -> Result<()> {
+        let valid = match *self {
+            LpPolicy::Burn => true,
+            LpPolicy::LockUntil { unlock_time } => unlock_time > sale_end_time,
+            LpPolicy::Vest { start_time, end_time } => start_time >= sale_end_time && end_time > start_time,
+        };
+
+        if !valid {
+            msg!("Invalid LP policy.");
+            return Err(ErrorCode::InvalidLpPolicy.into());
+        }
+
+        Ok(())
+    }
+
+    //计算截至当前时间可释放的LP总量（含已释放部分）
+    pub fn unlocked_amount(&self, total_amount: u64, current_time: i64) -> Result<u64> {
+        match *self {
+            LpPolicy::Burn => Ok(0),
+            LpPolicy::LockUntil { unlock_time } => {
+                Ok(if current_time >= unlock_time { total_amount } else { 0 })
+            }
+            LpPolicy::Vest { start_time, end_time } => {
+                if current_time <= start_time {
+                    return Ok(0);
+                }
+                if current_time >= end_time {
+                    return Ok(total_amount);
+                }
+
+                let unlocked = (total_amount as u128)
+                    .checked_mul((current_time - start_time) as u128)
+                    .ok_or(ErrorCode::Overflow)?
+                    .checked_div((end_time - start_time) as u128)
+                    .ok_or(ErrorCode::Overflow)?;
+
+                Ok(u64::try_from(unlocked).map_err(|_| ErrorCode::Overflow)?)
+            }
+        }
+    }
+}
+
+//LP锁仓账户，持有迁移后未销毁的LP代币
+#[account]
+pub struct LpLock {
+    pub sale: Pubkey, // 销售账户
+    pub owner: Pubkey, // 销售所有者，唯一可提取LP的地址
+    pub lp_mint: Pubkey, // LP代币Mint
+    pub policy: LpPolicy, // 释放规则
+    pub total_amount: u64, // 锁定的LP总量
+    pub released_amount: u64, // 已释放的LP数量
+    pub bump: u8, // PDA bump
+}
+
+/// 原生SOL托管账户中可用的lamports（扣除免租最低余额）
+pub fn sol_vault_balance(sol_vault: &AccountInfo) -> Result<u64> {
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    Ok(sol_vault.lamports().saturating_sub(rent_exempt))
+}
+
+/// 为系统账户PDA预存免租最低余额，使小额lamports转入不会因低于租金下限而失败
+/// 用于原生SOL销售的 sol_vault 及平台金库PDA
+pub fn fund_rent_exempt<'info>(
+    payer: &Signer<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(0);
+    let shortfall = rent_exempt.saturating_sub(account.lamports());
+    if shortfall == 0 {
+        return Ok(());
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+        ),
+        shortfall
+    )
+}
+
+/// 从原生SOL托管账户转出lamports，由 sol_vault 的种子签名
+pub fn transfer_from_sol_vault<'info>(
+    sol_vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    sale: Pubkey,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::SOL_VAULT_SEED, sale.as_ref(), &[bump]]];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: sol_vault.clone(),
+                to: to.clone(),
+            },
+            signer_seeds,
+        ),
+        amount
+    )
+}
+
+/// 从销售托管的代币账户转出，托管账户均由销售专属的 pda_account 持有，以其种子签名
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_from_vault<'info>(
+    pda_account: &AccountInfo<'info>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    sale: Pubkey,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_ACCOUNT, sale.as_ref(), &[bump]]];
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: from.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: pda_account.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+/// 按代币的转账手续费配置计算接收方实际到账的数量，未启用该扩展时原样返回
+pub fn net_transfer_amount(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let fee = match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(ErrorCode::Overflow)?,
+        Err(_) => 0,
+    };
+
+    Ok(amount.checked_sub(fee).ok_or(ErrorCode::Overflow)?)
+}
+
+/// 代币是否启用了转账手续费扩展
+pub fn has_transfer_fee(mint: &InterfaceAccount<Mint>) -> Result<bool> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(state.get_extension::<TransferFeeConfig>().is_ok())
+}
+
+/// 拒绝无法安全托管的 Token-2022 扩展：
+/// 永久委托可直接划走托管资金，不可转让及转账钩子会使托管转账失败，默认冻结会冻结新建的托管账户
+pub fn check_mint_extensions(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        if matches!(
+            extension,
+            ExtensionType::PermanentDelegate
+                | ExtensionType::NonTransferable
+                | ExtensionType::TransferHook
+                | ExtensionType::DefaultAccountState
+        ) {
+            msg!("Unsupported mint extension {:?}.", extension);
+            return Err(ErrorCode::UnsupportedMintExtension.into());
+        }
+    }
+
+    Ok(())
+}
+
+//平台全局配置（单例PDA）
+#[account]
+pub struct PlatformConfig {
+    pub admin: Pubkey, // 管理员
+    pub pending_admin: Pubkey, // 待接受的新管理员（未提名时为默认值）
+    pub treasury: Pubkey, // 平台金库
+    pub mint_fee_bps: u16, // mint手续费费率（万分比）
+    pub mint_fee_on_top: bool, // true: 手续费在购买金额之外另收; false: 从购买金额中扣除
+    pub mint_fee_refundable: bool, // 销售失败退款时是否退还手续费
+    pub migration_fee: u64, // 迁移至Raydium时收取的固定手续费（购买代币计价）
+    pub listing_fee: u64, // 发行方创建销售时收取的上架费
+    pub listing_fee_in_quote: bool, // true: 上架费以购买代币计价; false: 以SOL（lamports）计价
+    pub paused: bool, // 全局暂停
+    pub withdraw_when_paused: bool, // 暂停期间是否允许领取/退款/提取
+    pub max_sale_duration: i64, // 销售最长持续时间（秒），0表示不限制
+    pub cancel_with_purchases: bool, // 已有购买时是否允许所有者取消销售
+    pub amm_config: Pubkey, // 迁移建池使用的Raydium AmmConfig（决定手续费档位）
+    pub migration_timeout: i64, // 销售成功后等待迁移的最长时间（秒），超时未迁移则转为失败并开放退款
+    pub bump: u8, // PDA bump
+}
+
+//平台配置参数，initialize_config 与 update_config 共用
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigParams {
+    pub treasury: Pubkey,
+    pub mint_fee_bps: u16,
+    pub mint_fee_on_top: bool,
+    pub mint_fee_refundable: bool,
+    pub migration_fee: u64,
+    pub listing_fee: u64,
+    pub listing_fee_in_quote: bool,
+    pub withdraw_when_paused: bool,
+    pub max_sale_duration: i64,
+    pub cancel_with_purchases: bool,
+    pub amm_config: Pubkey,
+    pub migration_timeout: i64,
+}
+
+impl PlatformConfig {
+    pub fn apply(&mut self, params: ConfigParams) -> Result<()> {
+        // 验证手续费费率
+        if params.mint_fee_bps as u64 > crate::FEE_DENOMINATOR {
+            msg!("Fee rate is too high.");
+            return Err(ErrorCode::InvalidFeeRate.into());
+        }
+
+        // 迁移超时必须为正，保证成功但无法迁移的销售最终可以退款
+        if params.migration_timeout <= 0 {
+            msg!("Migration timeout must be positive.");
+            return Err(ErrorCode::InvalidMigrationTimeout.into());
+        }
+
+        self.treasury = params.treasury;
+        self.mint_fee_bps = params.mint_fee_bps;
+        self.mint_fee_on_top = params.mint_fee_on_top;
+        self.mint_fee_refundable = params.mint_fee_refundable;
+        self.migration_fee = params.migration_fee;
+        self.listing_fee = params.listing_fee;
+        self.listing_fee_in_quote = params.listing_fee_in_quote;
+        self.withdraw_when_paused = params.withdraw_when_paused;
+        self.max_sale_duration = params.max_sale_duration;
+        self.cancel_with_purchases = params.cancel_with_purchases;
+        self.amm_config = params.amm_config;
+        self.migration_timeout = params.migration_timeout;
+
+        Ok(())
+    }
+
+    //检查平台或销售是否处于暂停状态，领取/退款路径可按配置在暂停期间保持开放
+    pub fn check_paused(&self, sale_paused: bool, is_withdraw: bool) -> Result<()> {
+        if (self.paused || sale_paused) && !(is_withdraw && self.withdraw_when_paused) {
+            msg!("Paused.");
+            return Err(ErrorCode::Paused.into());
+        }
+
+        Ok(())
+    }
+
+    //按费率计算手续费
+    pub fn mint_fee(&self, amount: u64) -> Result<u64> {
+        let fee = (amount as u128)
+            .checked_mul(self.mint_fee_bps as u128)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_div(crate::FEE_DENOMINATOR as u128)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(u64::try_from(fee).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+//迁移手续费收取事件，供链下对账
+#[event]
+pub struct MigrationFeeCharged {
+    pub sale: Pubkey, // 销售账户
+    pub fee_mint: Pubkey, // 手续费代币Mint
+    pub amount: u64, // 手续费金额
 }
 
 //用户购买结构 
@@ -26,13 +550,148 @@ pub struct SaleAccount {
 pub struct UserPurchase {
     pub user_address: Pubkey, // 用户地址
     pub token_amount: u64, // 代币数量
-    pub token_price: u64, // 代币价格
+    pub price_numerator: u64, // 购买时的价格分子
+    pub price_denominator: u64, // 购买时的价格分母
     pub token_address: Pubkey, // 代币地址
     pub purchase_amount: u64, // 购买数量
     pub purchase_time: i64, // 购买时间
     pub is_claim: bool, // 是否已领取
+    pub fee_amount: u64, // 已支付的mint手续费
+    pub refundable_fee_amount: u64, // 退款时可退还的手续费（按每次购买时的配置累计）
+    pub purchase_count: u32, // 购买次数
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sale(price_numerator: u64, price_denominator: u64) -> SaleAccount {
+        SaleAccount {
+            owner: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            sale_amount: 0,
+            remaining_amount: 0,
+            price_numerator,
+            price_denominator,
+            buy_token_mint: Pubkey::default(),
+            start_time: 0,
+            end_time: 0,
+            status: SaleStatus::Active,
+            paused: false,
+            pool_state: Pubkey::default(),
+            lp_burned: 0,
+            lp_policy: LpPolicy::Burn,
+            max_per_wallet: 0,
+            min_per_purchase: 0,
+            soft_cap: 0,
+            burn_unsold: false,
+            total_claimed: 0,
+            total_refunded: 0,
+            open_positions: 0,
+            deposited_amount: 0,
+            escrowed_fees: 0,
+        }
+    }
 
+    //按整币价格换算为最小单位的分子分母：每个完整代币 price 个完整购买代币
+    fn whole_price(price: u64, token_decimals: u32, quote_decimals: u32) -> SaleAccount {
+        sale(price * 10u64.pow(quote_decimals), 10u64.pow(token_decimals))
+    }
 
+    #[test]
+    fn sale_amount_must_leave_liquidity() {
+        let params = SaleParams {
+            sale_amount: 1_000,
+            price_numerator: 1,
+            price_denominator: 1,
+            start_time: 0,
+            end_time: 1,
+            lp_policy: LpPolicy::Burn,
+            max_per_wallet: 0,
+            min_per_purchase: 0,
+            soft_cap: 0,
+            burn_unsold: false,
+        };
 
+        // 全部代币用于销售时没有可迁移的流动性
+        assert_eq!(params.validate(1_000, 0).unwrap_err(), ErrorCode::SaleAmountTooHigh.into());
+        assert_eq!(params.validate(999, 0).unwrap_err(), ErrorCode::SaleAmountTooHigh.into());
+    }
+
+    #[test]
+    fn prices_across_mint_decimals() {
+        // (代币精度, 购买代币精度, 整币价格, 预算, 代币数量, 金额)
+        let cases = [
+            (6, 6, 1, 1_000_000, 1_000_000, 1_000_000),
+            (9, 6, 2, 1_000_000, 500_000_000, 1_000_000),
+            (6, 9, 3, 1_000_000_000, 333_333, 999_999_000),
+            (0, 9, 5, 12_000_000_000, 2, 10_000_000_000),
+            (9, 0, 1, 7, 7_000_000_000, 7),
+            (18, 6, 1, 1, 1_000_000_000_000, 1),
+        ];
+
+        for (token_decimals, quote_decimals, price, budget, tokens, cost) in cases {
+            let sale = whole_price(price, token_decimals, quote_decimals);
+            assert_eq!(sale.tokens_for(budget).unwrap(), tokens);
+            assert_eq!(sale.cost_of(tokens).unwrap(), cost);
+        }
+    }
+
+    #[test]
+    fn price_below_one_quote_unit_per_token() {
+        // 每 2 个完整代币（9 位精度）仅 1 个购买代币最小单位
+        let sale = sale(1, 2_000_000_000);
+        assert_eq!(sale.tokens_for(3).unwrap(), 6_000_000_000);
+        assert_eq!(sale.cost_of(6_000_000_000).unwrap(), 3);
+        // 不足一个购买代币最小单位的数量向上取整
+        assert_eq!(sale.cost_of(1).unwrap(), 1);
+    }
+
+    #[test]
+    fn cost_never_exceeds_budget() {
+        let prices = [(1, 1), (3, 7), (7, 3), (5, 2), (1, 1_000_000_007), (999_999_937, 1), (u64::MAX, 3)];
+
+        for (numerator, denominator) in prices {
+            let sale = sale(numerator, denominator);
+            for budget in [0, 1, 2, 9, 10, 11, 999_999, 1_000_000_000, u64::MAX / 2] {
+                let tokens = sale.tokens_for(budget).unwrap();
+                if tokens == u64::MAX {
+                    continue;
+                }
+
+                // 应付金额不超过预算，且多买一个最小单位就会超出预算
+                assert!(sale.cost_of(tokens).unwrap() <= budget);
+                if let Ok(cost) = sale.cost_of(tokens + 1) {
+                    assert!(cost > budget);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tokens_for_saturates_at_u64_max() {
+        let sale = sale(1, u64::MAX);
+        assert_eq!(sale.tokens_for(u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(sale.tokens_for(2).unwrap(), u64::MAX);
+        assert_eq!(sale.tokens_for(1).unwrap(), u64::MAX);
+        assert_eq!(sale.tokens_for(0).unwrap(), 0);
+    }
+
+    #[test]
+    fn extreme_numerator_and_denominator() {
+        let expensive = sale(u64::MAX, 1);
+        assert_eq!(expensive.tokens_for(u64::MAX).unwrap(), 1);
+        assert_eq!(expensive.tokens_for(u64::MAX - 1).unwrap(), 0);
+        assert_eq!(expensive.cost_of(1).unwrap(), u64::MAX);
+        assert!(expensive.cost_of(2).is_err());
+
+        let cheap = sale(1, u64::MAX);
+        assert_eq!(cheap.cost_of(1).unwrap(), 1);
+        assert_eq!(cheap.cost_of(u64::MAX).unwrap(), 1);
+
+        let parity = sale(u64::MAX, u64::MAX);
+        assert_eq!(parity.tokens_for(u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(parity.cost_of(u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(parity.tokens_for(12_345).unwrap(), 12_345);
+    }
+}
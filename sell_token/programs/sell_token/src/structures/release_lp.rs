@@ -0,0 +1,97 @@
+use super::{SaleAccount, LpLock};
+use super::PlatformConfig;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount, Transfer, Mint, transfer};
+
+use super::error::ErrorCode;
+
+/// 销售所有者在锁定期满后提取LP代币
+#[derive(Accounts)]
+pub struct ReleaseLp<'info> {
+    #[account(
+        seeds = [crate::TOKEN_SEED, sale.token_mint.as_ref()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized,
+    )]
+    pub sale: Account<'info, SaleAccount>,
+
+    #[account(
+        mut,
+        seeds = [crate::LP_LOCK_SEED, sale.key().as_ref()],
+        bump = lp_lock.bump,
+        has_one = sale,
+        has_one = owner @ ErrorCode::Unauthorized,
+        has_one = lp_mint,
+    )]
+    pub lp_lock: Account<'info, LpLock>,
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub owner: Signer<'info>,
+
+    pub lp_mint: Account<'info, Mint>,
+
+    /// LP锁仓账户持有的LP代币账户
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = lp_lock,
+    )]
+    pub lp_lock_token_account: Account<'info, TokenAccount>,
+
+    /// 所有者接收LP的代币账户
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        constraint = owner_lp_token_account.owner == owner.key() @ ErrorCode::TokenAccountMismatch,
+    )]
+    pub owner_lp_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ReleaseLp<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, true)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // 计算当前可提取的数量
+        let unlocked = self.lp_lock.policy.unlocked_amount(self.lp_lock.total_amount, current_time)?;
+        let releasable = unlocked
+            .checked_sub(self.lp_lock.released_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        if releasable == 0 {
+            msg!("LP tokens are still locked.");
+            return Err(ErrorCode::LpLocked.into());
+        }
+
+        let sale_key = self.sale.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[crate::LP_LOCK_SEED, sale_key.as_ref(), &[self.lp_lock.bump]]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.lp_lock_token_account.to_account_info(),
+                to: self.owner_lp_token_account.to_account_info(),
+                authority: self.lp_lock.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer(transfer_ctx, releasable)?;
+
+        self.lp_lock.released_amount = self.lp_lock.released_amount
+            .checked_add(releasable)
+            .ok_or(ErrorCode::Overflow)?;
+
+        msg!("Released {} LP tokens to owner", releasable);
+
+        Ok(())
+    }
+}
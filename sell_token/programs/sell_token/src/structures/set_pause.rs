@@ -0,0 +1,57 @@
+use super::{PlatformConfig, SaleAccount};
+use anchor_lang::prelude::*;
+use super::error::ErrorCode;
+
+/// 管理员设置全局暂停
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        mut,
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetPause<'info> {
+    pub fn process(&mut self, paused: bool) -> Result<()> {
+        self.config.paused = paused;
+
+        msg!("Platform paused: {}", paused);
+
+        Ok(())
+    }
+}
+
+/// 管理员设置单个销售的暂停状态
+#[derive(Accounts)]
+pub struct SetSalePause<'info> {
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [crate::TOKEN_SEED, sale.token_mint.as_ref()],
+        bump
+    )]
+    pub sale: Account<'info, SaleAccount>,
+}
+
+impl<'info> SetSalePause<'info> {
+    pub fn process(&mut self, paused: bool) -> Result<()> {
+        self.sale.paused = paused;
+
+        msg!("Sale {} paused: {}", self.sale.token_mint, paused);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use super::{PlatformConfig, ConfigParams};
+use anchor_lang::prelude::*;
+use super::error::ErrorCode;
+
+/// 管理员更新平台配置
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn process(&mut self, params: ConfigParams) -> Result<()> {
+        self.config.apply(params)?;
+
+        msg!("Platform config updated, treasury {}, mint fee {} bps", self.config.treasury, self.config.mint_fee_bps);
+
+        Ok(())
+    }
+}
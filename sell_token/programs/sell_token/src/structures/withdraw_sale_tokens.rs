@@ -1,7 +1,8 @@
-use super::SaleAccount;
-use super::UserPurchase;
+use super::{SaleAccount, SaleStatus};
+use super::PlatformConfig;
+use super::transfer_from_vault;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Transfer, Mint, transfer};
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
 use anchor_spl::associated_token::AssociatedToken;
 
 use super::error::ErrorCode;
@@ -17,16 +18,24 @@ pub struct WithdrawSaleTokens<'info> {
     #[account(
         mut,
         seeds = [crate::TOKEN_SEED, token_mint.key().as_ref()],
-        bump
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized,
     )]
     pub sale: Account<'info, SaleAccount>,
 /// CHECK:` doc comment explaining why no checks through types are necessary.
     #[account(
         mut,
-        seeds = [crate::TOKEN_SEED], 
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
         bump,
     )]
-    pub pda_account: AccountInfo<'info>, //合约pda账户
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    /// 平台全局配置
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
     
     
     /// 销售代币的Mint账户
@@ -34,16 +43,18 @@ pub struct WithdrawSaleTokens<'info> {
     #[account(
         mut,
         constraint = token_mint.key() == sale.token_mint,
+        mint::token_program = token_program,
     )]
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     /// 购买代币的Mint账户
     /// 验证：必须与销售账户中记录的购买代币Mint一致
     #[account(
         mut,
         constraint = buy_token_mint.key() == sale.buy_token_mint,
+        mint::token_program = buy_token_program,
     )]
-    pub buy_token_mint: Account<'info, Mint>,
+    pub buy_token_mint: InterfaceAccount<'info, Mint>,
 
     /// 销售账户所有者
     /// 必须是交易的签名者
@@ -59,7 +70,7 @@ pub struct WithdrawSaleTokens<'info> {
         constraint = owner_token_account.owner == owner.key(),
         constraint = owner_token_account.mint == token_mint.key()
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
     
     /// 销售账户的代币账户
     /// 验证：
@@ -67,10 +78,12 @@ pub struct WithdrawSaleTokens<'info> {
     /// 2. 代币类型必须与销售代币一致
     #[account(
         mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
         constraint = sale_token_account.owner == pda_account.key(),
         constraint = sale_token_account.mint == token_mint.key()
     )]
-    pub sale_token_account: Account<'info, TokenAccount>,
+    pub sale_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// 所有者的购买代币账户
     /// 验证：
@@ -81,7 +94,7 @@ pub struct WithdrawSaleTokens<'info> {
         constraint = owner_buy_token_account.owner == owner.key(),
         constraint = owner_buy_token_account.mint == buy_token_mint.key()
     )]
-    pub owner_buy_token_account: Account<'info, TokenAccount>,
+    pub owner_buy_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// 合约的购买代币账户
     /// 验证：
@@ -89,15 +102,19 @@ pub struct WithdrawSaleTokens<'info> {
     /// 2. 代币类型必须与购买代币一致
     #[account(
         mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
         constraint = contract_token_account.owner == pda_account.key(),
         constraint = contract_token_account.mint == buy_token_mint.key()
     )]
-    pub contract_token_account: Account<'info, TokenAccount>,
+    pub contract_token_account: InterfaceAccount<'info, TokenAccount>,
     
     /// 系统程序
     pub system_program: Program<'info, System>,
-    /// 代币程序
-    pub token_program: Program<'info, Token>,
+    /// 销售代币的代币程序（SPL Token 或 Token-2022）
+    pub token_program: Interface<'info, TokenInterface>,
+    /// 购买代币的代币程序（SPL Token 或 Token-2022）
+    pub buy_token_program: Interface<'info, TokenInterface>,
     /// 关联代币程序
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -105,62 +122,47 @@ pub struct WithdrawSaleTokens<'info> {
 impl<'info> WithdrawSaleTokens<'info> {
     /// 处理代币提取的主要逻辑
     pub fn process(&mut self,bump_seed:u8) -> Result<()> {
-        // 获取当前时间
-        let current_time = Clock::get()?.unix_timestamp;
-        
-        // 检查销售是否已结束
-        if current_time < self.sale.end_time {
-            msg!("Sale has not ended yet.");
-            return Err(ErrorCode::SaleNotEnded.into());
-        }
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, true)?;
 
         // 获取销售账户中的代币余额
         let sale_balance = self.sale_token_account.amount;
         let contract_balance = self.contract_token_account.amount;
         
         // 处理代币提取逻辑
-        if sale_balance > 0 {
-            // 如果还有剩余代币（未卖完）
-            // 验证剩余代币数量是否与销售记录一致
-            if sale_balance != self.sale.remaining_amount {
+        if self.sale.status == SaleStatus::Failed && sale_balance > 0 {
+            // 销售失败，托管的代币全部退还所有者（购买代币留作用户退款）
+            // 验证托管代币不少于实际存入总量，他人额外转入的代币一并退还，不阻塞提取
+            if sale_balance < self.sale.deposited_amount {
                 msg!("Token balance mismatch.");
                 return Err(ErrorCode::BalanceMismatch.into());
             }
 
-            // 构建签名者种子
-            let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_SEED, &[bump_seed]]];
-
             // 转移剩余代币回所有者账户
-            let transfer_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                Transfer {
-                    from: self.sale_token_account.to_account_info(),
-                    to: self.owner_token_account.to_account_info(),
-                    authority: self.sale.to_account_info(),
-                },
-                signer_seeds,
-            );
-            transfer(transfer_ctx, sale_balance)?;
-
+            transfer_from_vault(
+                &self.pda_account,
+                &self.sale_token_account,
+                &self.owner_token_account,
+                &self.token_mint,
+                &self.token_program,
+                self.sale.key(),
+                bump_seed,
+                sale_balance,
+            )?;
 
             msg!("Withdrew {} unsold tokens back to owner", sale_balance);
-        } else if contract_balance > 0 {
-            // 如果代币已全部售出，转移购买代币到所有者账户
-            // 构建签名者种子
-            let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_SEED, &[bump_seed]]];
-
-            // 转移购买代币到所有者账户
-            let transfer_ctx = CpiContext::new_with_signer(
-                self.token_program.to_account_info(),
-                Transfer {
-                    from: self.contract_token_account.to_account_info(),
-                    to: self.owner_buy_token_account.to_account_info(),
-                    authority: self.sale.to_account_info(),
-                },
-                signer_seeds
-            );
-            transfer(transfer_ctx, contract_balance)?;
-
+        } else if self.sale.status == SaleStatus::Migrated && contract_balance > 0 {
+            // 流动性迁移后，剩余的购买代币转移到所有者账户
+            transfer_from_vault(
+                &self.pda_account,
+                &self.contract_token_account,
+                &self.owner_buy_token_account,
+                &self.buy_token_mint,
+                &self.buy_token_program,
+                self.sale.key(),
+                bump_seed,
+                contract_balance,
+            )?;
 
             msg!("Withdrew {} buy tokens to owner", contract_balance);
         } else {
@@ -169,10 +171,6 @@ impl<'info> WithdrawSaleTokens<'info> {
             return Err(ErrorCode::NoTokensToWithdraw.into());
         }
 
-        // 更新销售账户状态
-        self.sale.remaining_amount = 0;
-        self.sale.is_active = false;
-
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -1,8 +1,9 @@
-use super::SaleAccount;
+use super::{SaleAccount, SaleStatus};
+use super::PlatformConfig;
 use super::UserPurchase;
+use super::{transfer_from_sol_vault, transfer_from_vault};
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint};
-use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, Mint};
 use anchor_spl::associated_token::AssociatedToken;
 
 use super::error::ErrorCode;
@@ -17,23 +18,31 @@ pub struct WithdrawTokens<'info> {
 /// CHECK:` doc comment explaining why no checks through types are necessary.
     #[account(
         mut,
-        seeds = [crate::TOKEN_SEED], 
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref()],
         bump,
     )]
-    pub pda_account: AccountInfo<'info>, //合约pda账户
+    pub pda_account: AccountInfo<'info>, //销售专属的金库权限pda账户
+
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, PlatformConfig>,
     
 
     #[account(
         mut,
         constraint = token_mint.key() == sale.token_mint,
+        mint::token_program = token_program,
     )]
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(
         mut,
         constraint = buy_token_mint.key() == sale.buy_token_mint,
+        mint::token_program = buy_token_program,
     )]
-    pub buy_token_mint: Account<'info, Mint>,
+    pub buy_token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
@@ -43,31 +52,46 @@ pub struct WithdrawTokens<'info> {
         constraint = owner_token_account.owner == owner.key(),
         constraint = owner_token_account.mint == token_mint.key()
     )]
-    pub owner_token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), token_mint.key().as_ref()],
+        bump,
         constraint = sale_token_account.owner == pda_account.key(),
         constraint = sale_token_account.mint == token_mint.key()
     )]
-    pub sale_token_account: Account<'info, TokenAccount>,
+    pub sale_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    /// 用户接收退款的购买代币账户，原生SOL销售直接退还lamports时不需要
     #[account(
         mut,
         constraint = refund_token_account.owner == owner.key(),
         constraint = refund_token_account.mint == buy_token_mint.key()
     )]
-    pub refund_token_account: Account<'info, TokenAccount>,
+    pub refund_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
+        seeds = [crate::TOKEN_ACCOUNT, sale.key().as_ref(), buy_token_mint.key().as_ref()],
+        bump,
         constraint = contract_token_account.owner == pda_account.key(),
         constraint = contract_token_account.mint == buy_token_mint.key()
     )]
-    pub contract_token_account: Account<'info, TokenAccount>,
-    
+    pub contract_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: 原生SOL销售的lamports托管PDA
+    #[account(
+        mut,
+        seeds = [crate::SOL_VAULT_SEED, sale.key().as_ref()],
+        bump,
+    )]
+    pub sol_vault: AccountInfo<'info>,
+
+    /// 结算后关闭购买记录，租金退还买家
     #[account(
         mut,
+        close = owner,
         constraint = user_purchase.user_address == owner.key(),
         constraint = user_purchase.token_address == token_mint.key(),
         seeds = [crate::TOKEN_PURCHASE, owner.key().as_ref(),token_mint.key().as_ref()],
@@ -76,19 +100,26 @@ pub struct WithdrawTokens<'info> {
     pub user_purchase: Account<'info, UserPurchase>,
     
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub buy_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> WithdrawTokens<'info> {
-    pub fn process(&mut self,bump_seed:u8) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp;
-        
-        // 检查销售是否已结束
-        if current_time < self.sale.end_time {
-            msg!("Sale has not ended yet.");
-            return Err(ErrorCode::SaleNotEnded.into());
-        }
+    pub fn process(&mut self,bump_seed:u8, sol_vault_bump:u8) -> Result<()> {
+        // 检查平台或销售是否暂停
+        self.config.check_paused(self.sale.paused, true)?;
+
+        // 销售结束并结算后才能领取或退款
+        // 代币须在流动性迁移完成后才能领取，否则领取者可抢先以任意比例创建Raydium池，使迁移永久失败
+        let refund = match self.sale.status {
+            SaleStatus::Failed | SaleStatus::Cancelled => true,
+            SaleStatus::Migrated => false,
+            _ => {
+                msg!("Sale is not finalized.");
+                return Err(ErrorCode::InvalidSaleStatus.into());
+            }
+        };
 
         // 检查用户是否已购买
         if self.user_purchase.user_address != self.owner.key() {
@@ -96,9 +127,18 @@ impl<'info> WithdrawTokens<'info> {
             return Err(ErrorCode::UserNotPurchased.into());
         }   
 
+        // 结算后购买记录随即关闭，再次调用会因账户未初始化而失败；
+        // 关闭后也无法重新创建：购买记录只能在 buy_token 中创建，而结算时销售已不再处于 Active 状态。
+        // 重复领取由此保证，无需额外的领取标记
+
+        if self.user_purchase.token_amount == 0 && self.user_purchase.purchase_amount == 0 {
+            msg!("Nothing to withdraw.");
+            return Err(ErrorCode::NoTokensToWithdraw.into());
+        }
+
     
-        // 如果还有剩余代币（未卖完）
-        if self.sale.remaining_amount > 0 {
+        // 销售失败或已取消则退款
+        if refund {
             // 验证剩余代币数量是否与销售记录一致
             // if sale_balance != self.sale.remaining_amount {
             //     msg!("Token balance mismatch.");
@@ -108,82 +148,90 @@ impl<'info> WithdrawTokens<'info> {
             // 计算用户应得的退款金额
             let refund_amount = self.user_purchase.purchase_amount;
 
-            let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_SEED,  &[bump_seed]]];
-
-            // 生成从 GDTC 托管账户到用户 LP Token 账户的转账指令
-            let transfer_instruction = spl_token::instruction::transfer(
-                &self.token_program.key(),
-                &self.contract_token_account.key(),
-                &self.refund_token_account.key(),
-                &self.sale.key(),
-                &[],
-                refund_amount as u64, 
-            )?;
-       
-            // 执行带签名的 CPI 调用
-            invoke_signed(
-                &transfer_instruction,
-                &[
-                   self.token_program.to_account_info(),
-                   self.contract_token_account.to_account_info(),
-                   self.refund_token_account.to_account_info(),
-                   self.sale.to_account_info(),
-                ],
-                signer_seeds,
-            )?;
+            // 购买时配置为可退还的手续费托管在销售金库中，随本金一并退还
+            let fee_amount = self.user_purchase.refundable_fee_amount;
+            let total_amount = refund_amount
+                .checked_add(fee_amount)
+                .ok_or(ErrorCode::Overflow)?;
+
+            if self.sale.is_native() {
+                // 原生SOL销售直接退还lamports
+                transfer_from_sol_vault(
+                    &self.sol_vault,
+                    &self.owner.to_account_info(),
+                    &self.system_program,
+                    self.sale.key(),
+                    sol_vault_bump,
+                    total_amount,
+                )?;
+            } else {
+                // 从购买代币托管账户退款给用户
+                transfer_from_vault(
+                    &self.pda_account,
+                    &self.contract_token_account,
+                    self.refund_token_account()?,
+                    &self.buy_token_mint,
+                    &self.buy_token_program,
+                    self.sale.key(),
+                    bump_seed,
+                    total_amount,
+                )?;
+            }
+
+            if fee_amount > 0 {
+                self.sale.escrowed_fees = self.sale.escrowed_fees
+                    .checked_sub(fee_amount)
+                    .ok_or(ErrorCode::Overflow)?;
+
+                msg!("Refunded {} fee tokens to user", fee_amount);
+            }
 
             // 更新用户购买记录
             self.user_purchase.purchase_amount = 0;
             self.user_purchase.token_amount = 0;
+            self.user_purchase.fee_amount = 0;
+            self.user_purchase.refundable_fee_amount = 0;
+
+            self.sale.total_refunded = self.sale.total_refunded
+                .checked_add(refund_amount)
+                .ok_or(ErrorCode::Overflow)?;
 
             msg!("Refunded {} buy tokens to user", refund_amount);
         } else {
             // 如果代币已全部售出，发放用户购买的代币
             let token_amount = self.user_purchase.token_amount;
             
-            // 转移代币到用户账户
-            // transfer(
-            //     self.into_transfer_tokens_context(),
-            //     token_amount
-            // )?;
-
-            let signer_seeds: &[&[&[u8]]] = &[&[crate::TOKEN_SEED,   &[bump_seed]]];
-
-            // 生成从 GDTC 托管账户到用户 LP Token 账户的转账指令
-            let transfer_instruction = spl_token::instruction::transfer(
-                &self.token_program.key(),
-                &self.sale_token_account.key(),
-                &self.owner_token_account.key(),
-                &self.sale.key(),
-                &[],
-                token_amount as u64, 
-            )?;
-       
-            // 执行带签名的 CPI 调用
-            invoke_signed(
-                &transfer_instruction,
-                &[
-                   self.token_program.to_account_info(),
-                   self.sale_token_account.to_account_info(),
-                   self.owner_token_account.to_account_info(),
-                   self.sale.to_account_info(),
-                ],
-                signer_seeds,
+            // 从销售代币托管账户发放代币给用户
+            transfer_from_vault(
+                &self.pda_account,
+                &self.sale_token_account,
+                &self.owner_token_account,
+                &self.token_mint,
+                &self.token_program,
+                self.sale.key(),
+                bump_seed,
+                token_amount,
             )?;
 
             // 更新用户购买记录
             self.user_purchase.purchase_amount = 0;
             self.user_purchase.token_amount = 0;
 
+            self.sale.total_claimed = self.sale.total_claimed
+                .checked_add(token_amount)
+                .ok_or(ErrorCode::Overflow)?;
+
             msg!("Distributed {} tokens to user", token_amount);
         }
 
-        // 更新销售账户状态
-        self.sale.remaining_amount = 0;
-        self.sale.is_active = false;
+        self.sale.open_positions = self.sale.open_positions
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
 
         Ok(())
     }
 
- 
+    fn refund_token_account(&self) -> Result<&InterfaceAccount<'info, TokenAccount>> {
+        Ok(self.refund_token_account.as_ref().ok_or(ErrorCode::TokenAccountMismatch)?)
+    }
 }
@@ -0,0 +1,133 @@
+use super::PlatformConfig;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{TokenInterface, TokenAccount, TransferChecked, Mint, transfer_checked};
+
+use super::error::ErrorCode;
+
+/// 管理员将金库PDA中累积的手续费提取到配置的平台金库地址
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: 金库PDA，仅作为手续费代币账户的权限账户
+    #[account(
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// 金库PDA持有的手续费代币账户
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// 接收账户，必须属于配置中的平台金库地址
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = destination_token_account.owner == config.treasury @ ErrorCode::TokenAccountMismatch,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> WithdrawTreasury<'info> {
+    pub fn process(&mut self, amount: u64, bump_seed: u8) -> Result<()> {
+        if amount == 0 || amount > self.treasury_token_account.amount {
+            msg!("Insufficient treasury balance.");
+            return Err(ErrorCode::InsufficientBalance.into());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[crate::TREASURY_SEED, &[bump_seed]]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            TransferChecked {
+                from: self.treasury_token_account.to_account_info(),
+                mint: self.mint.to_account_info(),
+                to: self.destination_token_account.to_account_info(),
+                authority: self.treasury.to_account_info(),
+            },
+            signer_seeds,
+        );
+        transfer_checked(transfer_ctx, amount, self.mint.decimals)?;
+
+        msg!("Withdrew {} fee tokens to treasury {}", amount, self.config.treasury);
+
+        Ok(())
+    }
+}
+
+/// 管理员将金库PDA中累积的SOL手续费提取到配置的平台金库地址
+#[derive(Accounts)]
+pub struct WithdrawTreasurySol<'info> {
+    #[account(
+        seeds = [crate::CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        has_one = treasury @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, PlatformConfig>,
+
+    pub admin: Signer<'info>,
+
+    /// CHECK: 金库PDA，持有SOL计价的手续费
+    #[account(
+        mut,
+        seeds = [crate::TREASURY_SEED],
+        bump,
+    )]
+    pub treasury_vault: AccountInfo<'info>,
+
+    /// CHECK: 配置中的平台金库地址，由 has_one 校验
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawTreasurySol<'info> {
+    pub fn process(&mut self, amount: u64, bump_seed: u8) -> Result<()> {
+        // 金库PDA始终保留免租最低余额，否则之后低于该额度的手续费无法转入
+        let balance = self.treasury_vault.lamports();
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let remaining = balance.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+        if amount == 0 || remaining < rent_exempt {
+            msg!("Insufficient treasury balance.");
+            return Err(ErrorCode::InsufficientBalance.into());
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[&[crate::TREASURY_SEED, &[bump_seed]]];
+
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: self.treasury_vault.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount
+        )?;
+
+        msg!("Withdrew {} lamports to treasury {}", amount, self.config.treasury);
+
+        Ok(())
+    }
+}
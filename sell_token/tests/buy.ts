@@ -0,0 +1,109 @@
+import { expect } from "chai";
+import { BN } from "@coral-xyz/anchor";
+import {
+  buy,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  program,
+  userPurchasePda,
+} from "./helpers";
+
+describe("buy", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("accumulates repeat purchases into one position", async () => {
+    const s = await createSale();
+    const buyer = await createBuyer(s, 3_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+    await buy(s, buyer, 2_000_000n);
+
+    const purchase = await program.account.userPurchase.fetch(
+      userPurchasePda(buyer.keypair.publicKey, s.tokenMint)
+    );
+    expect(purchase.tokenAmount.toString()).to.equal("3000000000");
+    expect(purchase.purchaseAmount.toString()).to.equal("3000000");
+    expect(purchase.purchaseCount).to.equal(2);
+
+    // 同一钱包多次购买只算一条待结算记录
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.openPositions).to.equal(1);
+    expect(sale.remainingAmount.toString()).to.equal("497000000000");
+  });
+
+  it("rejects purchases beyond the per-wallet cap", async () => {
+    const s = await createSale({ maxPerWallet: 1_500_000_000n });
+    const buyer = await createBuyer(s, 2_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+
+    // 累计 2_000_000_000 超过上限 1_500_000_000
+    try {
+      await buy(s, buyer, 1_000_000n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("WalletCapExceeded");
+    }
+  });
+
+  it("rejects purchases below the minimum", async () => {
+    const s = await createSale({ minPerPurchase: 1_000_000_000n });
+    const buyer = await createBuyer(s, 500_000n);
+
+    try {
+      await buy(s, buyer, 500_000n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("BelowMinimumPurchase");
+    }
+  });
+
+  it("allows buying the last tokens below the minimum", async () => {
+    const s = await createSale({
+      saleAmount: 250_000_000_000n,
+      minPerPurchase: 200_000_000_000n,
+    });
+    const first = await createBuyer(s, 200_000_000n);
+    const last = await createBuyer(s, 50_000_000n);
+
+    await buy(s, first, 200_000_000n);
+
+    // 剩余 50_000_000_000 不足最小购买量，允许一次买完
+    await buy(s, last, 50_000_000n);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.remainingAmount.toNumber()).to.equal(0);
+    expect(sale.status).to.deep.equal({ succeeded: {} });
+  });
+
+  it("rejects purchases before the start time", async () => {
+    const s = await createSale({ startDelay: 3600 });
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.status).to.deep.equal({ pending: {} });
+
+    try {
+      await buy(s, buyer, 1_000_000n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("SaleNotStarted");
+    }
+  });
+
+  it("rejects sales longer than the maximum duration", async () => {
+    await ensureConfig({ maxSaleDuration: new BN(60) });
+
+    try {
+      await createSale({ duration: 3600 });
+      expect.fail("init should fail");
+    } catch (err) {
+      expect(String(err)).to.include("SaleDurationTooLong");
+    }
+
+    await ensureConfig();
+  });
+});
@@ -0,0 +1,188 @@
+import { expect } from "chai";
+import { BN } from "@coral-xyz/anchor";
+import {
+  Buyer,
+  balance,
+  buy,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  migrate,
+  program,
+  provider,
+  sleep,
+  userPurchasePda,
+  withdrawTokens,
+} from "./helpers";
+
+describe("claim", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  // 按各种顺序结算，校验每个买家只拿到自己的份额且销售的共享状态不被改写
+  const orders: [string, (n: number) => number[]][] = [
+    ["purchase order", (n) => [...Array(n).keys()]],
+    ["reverse order", (n) => [...Array(n).keys()].reverse()],
+    ["interleaved order", (n) => [1, 3, 0, 2].filter((i) => i < n)],
+  ];
+
+  for (const [name, order] of orders) {
+    it(`every buyer of a failed sale gets exactly their refund in ${name}`, async () => {
+      const s = await createSale({ softCap: 500_000_000_000n, duration: 4 });
+      const amounts = [3_000_000n, 1_000_000n, 2_000_000n, 4_000_000n];
+
+      const buyers: Buyer[] = [];
+      for (const amount of amounts) {
+        const buyer = await createBuyer(s, amount);
+        await buy(s, buyer, amount);
+        buyers.push(buyer);
+      }
+
+      const before = await program.account.saleAccount.fetch(s.sale);
+
+      await sleep(6000);
+      await finalize(s);
+
+      let refunded = 0n;
+      for (const i of order(buyers.length)) {
+        await withdrawTokens(s, buyers[i]);
+        refunded += amounts[i];
+
+        expect(await balance(buyers[i].buyTokenAccount)).to.equal(amounts[i]);
+        expect(await balance(buyers[i].tokenAccount)).to.equal(0n);
+
+        const sale = await program.account.saleAccount.fetch(s.sale);
+        expect(sale.status).to.deep.equal({ failed: {} });
+        expect(BigInt(sale.totalRefunded.toString())).to.equal(refunded);
+        expect(BigInt(sale.totalClaimed.toString())).to.equal(0n);
+        expect(sale.remainingAmount.toString()).to.equal(before.remainingAmount.toString());
+      }
+
+      expect(await balance(s.quoteVault)).to.equal(0n);
+    });
+
+    it(`every buyer of a migrated sale claims exactly their tokens in ${name}`, async () => {
+      const s = await createSale({ duration: 4 });
+      // 合计 5 亿购买代币最小单位，恰好售罄
+      const amounts = [150_000_000n, 50_000_000n, 100_000_000n, 200_000_000n];
+
+      const buyers: Buyer[] = [];
+      for (const amount of amounts) {
+        const buyer = await createBuyer(s, amount);
+        await buy(s, buyer, amount);
+        buyers.push(buyer);
+      }
+
+      await sleep(6000);
+      await finalize(s);
+      await migrate(s);
+
+      let claimed = 0n;
+      for (const i of order(buyers.length)) {
+        await withdrawTokens(s, buyers[i]);
+        claimed += amounts[i] * 1_000n;
+
+        expect(await balance(buyers[i].tokenAccount)).to.equal(amounts[i] * 1_000n);
+        expect(await balance(buyers[i].buyTokenAccount)).to.equal(0n);
+
+        const sale = await program.account.saleAccount.fetch(s.sale);
+        expect(sale.status).to.deep.equal({ migrated: {} });
+        expect(BigInt(sale.totalClaimed.toString())).to.equal(claimed);
+        expect(BigInt(sale.totalRefunded.toString())).to.equal(0n);
+        expect(sale.remainingAmount.toNumber()).to.equal(0);
+        expect(await balance(s.saleVault)).to.equal(500_000_000_000n - claimed);
+      }
+    });
+  }
+
+  it("rejects a second refund by the same buyer", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const first = await createBuyer(s, 4_000_000n);
+    const second = await createBuyer(s, 1_000_000n);
+
+    await buy(s, first, 4_000_000n);
+    await buy(s, second, 1_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+
+    const purchase = userPurchasePda(first.keypair.publicKey, s.tokenMint);
+    const lamportsBefore = await provider().connection.getBalance(first.keypair.publicKey);
+
+    await withdrawTokens(s, first);
+
+    // 购买记录已关闭，租金退还买家
+    expect(await program.account.userPurchase.fetchNullable(purchase)).to.be.null;
+    expect(await provider().connection.getBalance(first.keypair.publicKey)).to.be.greaterThan(
+      lamportsBefore
+    );
+
+    try {
+      await withdrawTokens(s, first);
+      expect.fail("second refund should fail");
+    } catch (err) {
+      // 购买记录已关闭，无法再次结算
+      expect(String(err)).to.include("AccountNotInitialized");
+    }
+
+    await withdrawTokens(s, second);
+    expect(await balance(second.buyTokenAccount)).to.equal(1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(0n);
+  });
+
+  it("rejects a second claim by the same buyer", async () => {
+    const s = await createSale({ duration: 2 });
+    const buyer = await createBuyer(s, 500_000_000n);
+    await buy(s, buyer, 500_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+    await migrate(s);
+
+    await withdrawTokens(s, buyer);
+    expect(await balance(buyer.tokenAccount)).to.equal(500_000_000_000n);
+
+    try {
+      await withdrawTokens(s, buyer);
+      expect.fail("second claim should fail");
+    } catch (err) {
+      expect(String(err)).to.include("AccountNotInitialized");
+    }
+  });
+
+  it("turns a succeeded sale into a refundable one after the migration timeout", async () => {
+    await ensureConfig({ migrationTimeout: new BN(8) });
+    try {
+      const s = await createSale({ softCap: 1n, duration: 2 });
+      const buyer = await createBuyer(s, 1_000_000n);
+      await buy(s, buyer, 1_000_000n);
+
+      await sleep(4000);
+      await finalize(s);
+      expect((await program.account.saleAccount.fetch(s.sale)).status).to.deep.equal({
+        succeeded: {},
+      });
+
+      // 迁移期限内不能转为失败
+      try {
+        await finalize(s);
+        expect.fail("finalize should wait for the migration timeout");
+      } catch (err) {
+        expect(String(err)).to.include("InvalidSaleStatus");
+      }
+
+      await sleep(9000);
+      await finalize(s);
+      expect((await program.account.saleAccount.fetch(s.sale)).status).to.deep.equal({
+        failed: {},
+      });
+
+      await withdrawTokens(s, buyer);
+      expect(await balance(buyer.buyTokenAccount)).to.equal(1_000_000n);
+    } finally {
+      await ensureConfig();
+    }
+  });
+});
@@ -0,0 +1,115 @@
+import { expect } from "chai";
+import { BN } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddressSync,
+  getMint,
+} from "@solana/spl-token";
+import {
+  airdrop,
+  balance,
+  configPda,
+  ensureConfig,
+  newMint,
+  now,
+  payer,
+  program,
+  provider,
+  salePda,
+  solVaultPda,
+  treasuryPda,
+  vaultAuthorityPda,
+  vaultPda,
+} from "./helpers";
+
+const METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+describe("create token and sale", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("creates the mint, its metadata and the sale in one instruction", async () => {
+    const owner = Keypair.generate();
+    await airdrop(owner.publicKey);
+
+    const nonce = new BN(1);
+    const decimals = 6;
+    const supply = 1_000_000n * 10n ** BigInt(decimals);
+
+    const tokenMint = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_mint"), owner.publicKey.toBuffer(), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const metadata = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), METADATA_PROGRAM_ID.toBuffer(), tokenMint.toBuffer()],
+      METADATA_PROGRAM_ID
+    )[0];
+    const buyTokenMint = await newMint(payer().publicKey, 6, TOKEN_PROGRAM_ID);
+    const sale = salePda(tokenMint);
+    const start = now();
+
+    await program.methods
+      .createTokenAndSale(
+        {
+          saleAmount: new BN((supply / 2n).toString()),
+          priceNumerator: new BN(1_000),
+          priceDenominator: new BN(10 ** decimals),
+          startTime: new BN(start - 1),
+          endTime: new BN(start + 3600),
+          lpPolicy: { burn: {} },
+          maxPerWallet: new BN(0),
+          minPerPurchase: new BN(0),
+          softCap: new BN(0),
+          burnUnsold: false,
+        },
+        nonce,
+        decimals,
+        new BN(supply.toString()),
+        "Test Token",
+        "TEST",
+        "https://example.com/token.json"
+      )
+      .accountsPartial({
+        sale,
+        pdaAccount: vaultAuthorityPda(sale),
+        config: configPda(),
+        tokenMint,
+        buyTokenMint,
+        owner: owner.publicKey,
+        saleTokenAccount: vaultPda(sale, tokenMint),
+        contractTokenAccount: vaultPda(sale, buyTokenMint),
+        solVault: solVaultPda(sale),
+        treasury: treasuryPda(),
+        treasuryTokenAccount: getAssociatedTokenAddressSync(buyTokenMint, treasuryPda(), true),
+        ownerBuyTokenAccount: null,
+        metadata,
+        metadataProgram: METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        buyTokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([owner])
+      .rpc();
+
+    // 全部供应量铸造到销售托管账户，铸币权限由合约PDA持有直至迁移
+    const mint = await getMint(provider().connection, tokenMint);
+    expect(mint.supply).to.equal(supply);
+    expect(mint.decimals).to.equal(decimals);
+    expect(mint.mintAuthority.toBase58()).to.equal(vaultAuthorityPda(sale).toBase58());
+    expect(await balance(vaultPda(sale, tokenMint))).to.equal(supply);
+
+    // 元数据账户由 Metaplex 程序创建
+    const metadataAccount = await provider().connection.getAccountInfo(metadata);
+    expect(metadataAccount.owner.toBase58()).to.equal(METADATA_PROGRAM_ID.toBase58());
+    expect(metadataAccount.data.includes(Buffer.from("Test Token"))).to.be.true;
+
+    const saleAccount = await program.account.saleAccount.fetch(sale);
+    expect(saleAccount.owner.toBase58()).to.equal(owner.publicKey.toBase58());
+    expect(saleAccount.depositedAmount.toString()).to.equal(supply.toString());
+  });
+});
@@ -0,0 +1,62 @@
+import { expect } from "chai";
+import { BN } from "@coral-xyz/anchor";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  Sale,
+  balance,
+  buy,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  migrate,
+  provider,
+  sleep,
+  treasuryPda,
+} from "./helpers";
+
+const treasuryTokenAccount = (s: Sale) =>
+  getAssociatedTokenAddressSync(s.buyTokenMint, treasuryPda(), true, s.buyTokenProgram);
+
+describe("fees", () => {
+  afterEach(async () => {
+    await ensureConfig();
+  });
+
+  it("charges the migration fee from the raised funds", async () => {
+    await ensureConfig({ migrationFee: new BN(1_000_000) });
+
+    const s = await createSale({ duration: 2 });
+    const buyer = await createBuyer(s, 500_000_000n);
+    await buy(s, buyer, 500_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+    const pool = await migrate(s);
+
+    expect(await balance(treasuryTokenAccount(s))).to.equal(1_000_000n);
+    // 扣除手续费后的资金按销售价格配对，多余的流动性代币退还所有者
+    expect(await balance(pool.buyTokenVault)).to.equal(499_000_000n);
+    expect(await balance(pool.saleTokenVault)).to.equal(499_000_000_000n);
+    expect(await balance(s.ownerTokenAccount)).to.equal(1_000_000_000n);
+  });
+
+  it("charges the listing fee in lamports", async () => {
+    await ensureConfig({ listingFee: new BN(1_000_000) });
+
+    const conn = provider().connection;
+    const before = await conn.getBalance(treasuryPda());
+    await createSale();
+
+    expect(await conn.getBalance(treasuryPda())).to.equal(before + 1_000_000);
+  });
+
+  it("charges the listing fee in the quote token", async () => {
+    await ensureConfig({ listingFee: new BN(2_000_000), listingFeeInQuote: true });
+
+    const s = await createSale({ ownerQuote: 2_000_000n });
+
+    expect(await balance(treasuryTokenAccount(s))).to.equal(2_000_000n);
+    expect(await balance(s.ownerBuyTokenAccount)).to.equal(0n);
+  });
+});
@@ -0,0 +1,605 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  LAMPORTS_PER_SOL,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  getMintLen,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { SellToken2 } from "../target/types/sell_token2";
+
+export const program = anchor.workspace.sellToken2 as Program<SellToken2>;
+
+const seed = (s: string) => Buffer.from(s);
+
+export const configPda = () =>
+  PublicKey.findProgramAddressSync([seed("platform_config")], program.programId)[0];
+
+export const treasuryPda = () =>
+  PublicKey.findProgramAddressSync([seed("treasury")], program.programId)[0];
+
+export const salePda = (mint: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [seed("token_sale"), mint.toBuffer()],
+    program.programId
+  )[0];
+
+export const vaultAuthorityPda = (sale: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [seed("token_account"), sale.toBuffer()],
+    program.programId
+  )[0];
+
+export const vaultPda = (sale: PublicKey, mint: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [seed("token_account"), sale.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+
+export const solVaultPda = (sale: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [seed("sol_vault"), sale.toBuffer()],
+    program.programId
+  )[0];
+
+export const userPurchasePda = (user: PublicKey, mint: PublicKey) =>
+  PublicKey.findProgramAddressSync(
+    [seed("token_purchase"), user.toBuffer(), mint.toBuffer()],
+    program.programId
+  )[0];
+
+export const lpLockPda = (sale: PublicKey) =>
+  PublicKey.findProgramAddressSync([seed("lp_lock"), sale.toBuffer()], program.programId)[0];
+
+// Raydium CP-Swap 主网部署，由本地验证节点克隆（见 Anchor.toml）
+export const CP_SWAP_PROGRAM_ID = new PublicKey("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+export const AMM_CONFIG = new PublicKey("D4FPEruKEHrG5TenZ2mpDGEfu1iUvTiqBxvpU8HLBvC2");
+export const CREATE_POOL_FEE_RECEIVER = new PublicKey("DNXgeM9EiiaAbaWvwjHj9fQQLAX5ZsfHyvmYUNRAdNC8");
+
+const cpSwapPda = (...seeds: Buffer[]) =>
+  PublicKey.findProgramAddressSync(seeds, CP_SWAP_PROGRAM_ID)[0];
+
+// Raydium要求 token_0_mint < token_1_mint
+export const sortMints = (a: PublicKey, b: PublicKey): [PublicKey, PublicKey] =>
+  Buffer.compare(a.toBuffer(), b.toBuffer()) < 0 ? [a, b] : [b, a];
+
+export function poolAccounts(tokenMint: PublicKey, buyTokenMint: PublicKey) {
+  const [mint0, mint1] = sortMints(tokenMint, buyTokenMint);
+  const poolState = cpSwapPda(seed("pool"), AMM_CONFIG.toBuffer(), mint0.toBuffer(), mint1.toBuffer());
+  return {
+    authority: cpSwapPda(seed("vault_and_lp_mint_auth_seed")),
+    poolState,
+    lpMint: cpSwapPda(seed("pool_lp_mint"), poolState.toBuffer()),
+    saleTokenVault: cpSwapPda(seed("pool_vault"), poolState.toBuffer(), tokenMint.toBuffer()),
+    buyTokenVault: cpSwapPda(seed("pool_vault"), poolState.toBuffer(), buyTokenMint.toBuffer()),
+    observationState: cpSwapPda(seed("observation"), poolState.toBuffer()),
+  };
+}
+
+export const sleep = (ms: number) => new Promise((r) => setTimeout(r, ms));
+
+export const now = () => Math.floor(Date.now() / 1000);
+
+export const provider = () => anchor.getProvider() as anchor.AnchorProvider;
+
+export const payer = () => (provider().wallet as anchor.Wallet).payer;
+
+export async function airdrop(to: PublicKey, sol = 10) {
+  const conn = provider().connection;
+  const sig = await conn.requestAirdrop(to, sol * LAMPORTS_PER_SOL);
+  await conn.confirmTransaction(sig, "confirmed");
+}
+
+// 按账户所属的代币程序读取余额，兼容 SPL Token 与 Token-2022
+export async function balance(account: PublicKey): Promise<bigint> {
+  const conn = provider().connection;
+  const info = await conn.getAccountInfo(account);
+  return (await getAccount(conn, account, undefined, info.owner)).amount;
+}
+
+export async function tokenAccount(
+  mint: PublicKey,
+  owner: PublicKey,
+  programId: PublicKey = TOKEN_PROGRAM_ID
+) {
+  const account = await getOrCreateAssociatedTokenAccount(
+    provider().connection,
+    payer(),
+    mint,
+    owner,
+    true,
+    undefined,
+    undefined,
+    programId
+  );
+  return account.address;
+}
+
+export const defaultConfig = () => ({
+  treasury: provider().wallet.publicKey,
+  mintFeeBps: 0,
+  mintFeeOnTop: false,
+  mintFeeRefundable: false,
+  migrationFee: new BN(0),
+  listingFee: new BN(0),
+  listingFeeInQuote: false,
+  withdrawWhenPaused: true,
+  maxSaleDuration: new BN(0),
+  cancelWithPurchases: false,
+  ammConfig: AMM_CONFIG,
+  migrationTimeout: new BN(7 * 24 * 3600),
+});
+
+// 平台配置是全局单例，首次调用时初始化，之后按需更新
+export async function ensureConfig(overrides: Partial<ReturnType<typeof defaultConfig>> = {}) {
+  const params = { ...defaultConfig(), ...overrides };
+  const existing = await program.account.platformConfig.fetchNullable(configPda());
+
+  if (existing) {
+    await program.methods.updateConfig(params).rpc();
+    return;
+  }
+
+  const programData = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  )[0];
+
+  await program.methods
+    .initializeConfig(params)
+    .accountsPartial({
+      config: configPda(),
+      admin: provider().wallet.publicKey,
+      program: program.programId,
+      programData,
+      treasury: treasuryPda(),
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+}
+
+export interface SaleOptions {
+  supply?: bigint;
+  decimals?: number;
+  quoteDecimals?: number;
+  saleAmount?: bigint;
+  // 价格为 priceDenominator 个代币最小单位对应 priceNumerator 个购买代币最小单位
+  priceNumerator?: bigint;
+  priceDenominator?: bigint;
+  duration?: number;
+  // 开始时间相对当前时间的延迟（秒），默认立即开始
+  startDelay?: number;
+  softCap?: bigint;
+  maxPerWallet?: bigint;
+  minPerPurchase?: bigint;
+  native?: boolean;
+  tokenProgram?: PublicKey;
+  buyTokenProgram?: PublicKey;
+  tokenExtensions?: MintExtensions;
+  buyTokenExtensions?: MintExtensions;
+  lpPolicy?: any;
+  burnUnsold?: boolean;
+  // 预先发给所有者的购买代币数量，用于支付以购买代币计价的上架费
+  ownerQuote?: bigint;
+}
+
+// Token-2022 代币扩展，设置任一项时代币由 Token-2022 程序创建
+export interface MintExtensions {
+  transferFeeBps?: number;
+  permanentDelegate?: PublicKey;
+}
+
+export async function newMint(
+  authority: PublicKey,
+  decimals: number,
+  programId: PublicKey,
+  extensions?: MintExtensions
+): Promise<PublicKey> {
+  const conn = provider().connection;
+  if (!extensions) {
+    return createMint(conn, payer(), authority, null, decimals, undefined, undefined, programId);
+  }
+
+  const mint = Keypair.generate();
+  const types: ExtensionType[] = [];
+  if (extensions.transferFeeBps !== undefined) types.push(ExtensionType.TransferFeeConfig);
+  if (extensions.permanentDelegate) types.push(ExtensionType.PermanentDelegate);
+
+  const space = getMintLen(types);
+  const tx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: payer().publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports: await conn.getMinimumBalanceForRentExemption(space),
+      programId: TOKEN_2022_PROGRAM_ID,
+    })
+  );
+  if (extensions.transferFeeBps !== undefined) {
+    tx.add(
+      createInitializeTransferFeeConfigInstruction(
+        mint.publicKey,
+        payer().publicKey,
+        payer().publicKey,
+        extensions.transferFeeBps,
+        1_000_000_000_000_000n,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+  }
+  if (extensions.permanentDelegate) {
+    tx.add(
+      createInitializePermanentDelegateInstruction(
+        mint.publicKey,
+        extensions.permanentDelegate,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+  }
+  tx.add(createInitializeMintInstruction(mint.publicKey, decimals, authority, null, TOKEN_2022_PROGRAM_ID));
+
+  await sendAndConfirmTransaction(conn, tx, [payer(), mint]);
+  return mint.publicKey;
+}
+
+export interface Sale {
+  owner: Keypair;
+  tokenMint: PublicKey;
+  buyTokenMint: PublicKey;
+  sale: PublicKey;
+  vaultAuthority: PublicKey;
+  saleVault: PublicKey;
+  quoteVault: PublicKey;
+  solVault: PublicKey;
+  native: boolean;
+  tokenProgram: PublicKey;
+  buyTokenProgram: PublicKey;
+  ownerTokenAccount: PublicKey;
+  ownerBuyTokenAccount: PublicKey;
+}
+
+// 创建新代币并以其全部供应量开启销售
+export async function createSale(opts: SaleOptions = {}): Promise<Sale> {
+  const conn = provider().connection;
+  const decimals = opts.decimals ?? 6;
+  const supply = opts.supply ?? 1_000_000n * 10n ** BigInt(decimals);
+  const saleAmount = opts.saleAmount ?? supply / 2n;
+
+  const tokenProgram = opts.tokenExtensions
+    ? TOKEN_2022_PROGRAM_ID
+    : opts.tokenProgram ?? TOKEN_PROGRAM_ID;
+  const buyTokenProgram = opts.buyTokenExtensions
+    ? TOKEN_2022_PROGRAM_ID
+    : opts.buyTokenProgram ?? TOKEN_PROGRAM_ID;
+
+  const owner = Keypair.generate();
+  await airdrop(owner.publicKey);
+
+  const tokenMint = await newMint(owner.publicKey, decimals, tokenProgram, opts.tokenExtensions);
+  // 原生SOL销售以WSOL作为购买代币
+  const buyTokenMint = opts.native
+    ? NATIVE_MINT
+    : await newMint(
+        payer().publicKey,
+        opts.quoteDecimals ?? 6,
+        buyTokenProgram,
+        opts.buyTokenExtensions
+      );
+
+  const ownerTokenAccount = await tokenAccount(tokenMint, owner.publicKey, tokenProgram);
+  const ownerBuyTokenAccount = await tokenAccount(buyTokenMint, owner.publicKey, buyTokenProgram);
+  await mintTo(conn, payer(), tokenMint, ownerTokenAccount, owner, supply, [], undefined, tokenProgram);
+  if (opts.ownerQuote) {
+    await mintTo(
+      conn,
+      payer(),
+      buyTokenMint,
+      ownerBuyTokenAccount,
+      payer(),
+      opts.ownerQuote,
+      [],
+      undefined,
+      buyTokenProgram
+    );
+  }
+
+  const sale = salePda(tokenMint);
+  const start = now();
+
+  await program.methods
+    .initSaleAccount({
+      saleAmount: new BN(saleAmount.toString()),
+      // 默认每个完整代币 1000 个购买代币最小单位
+      priceNumerator: new BN((opts.priceNumerator ?? 1_000n).toString()),
+      priceDenominator: new BN((opts.priceDenominator ?? 10n ** BigInt(decimals)).toString()),
+      startTime: new BN(start + (opts.startDelay ?? -1)),
+      endTime: new BN(start + (opts.startDelay ?? 0) + (opts.duration ?? 3600)),
+      lpPolicy: opts.lpPolicy ?? { burn: {} },
+      maxPerWallet: new BN((opts.maxPerWallet ?? 0n).toString()),
+      minPerPurchase: new BN((opts.minPerPurchase ?? 0n).toString()),
+      softCap: new BN((opts.softCap ?? 0n).toString()),
+      burnUnsold: opts.burnUnsold ?? false,
+    })
+    .accountsPartial({
+      sale,
+      pdaAccount: vaultAuthorityPda(sale),
+      config: configPda(),
+      tokenMint,
+      buyTokenMint,
+      owner: owner.publicKey,
+      ownerTokenAccount,
+      saleTokenAccount: vaultPda(sale, tokenMint),
+      contractTokenAccount: vaultPda(sale, buyTokenMint),
+      solVault: solVaultPda(sale),
+      treasury: treasuryPda(),
+      ownerBuyTokenAccount: opts.ownerQuote ? ownerBuyTokenAccount : null,
+      tokenProgram,
+      buyTokenProgram,
+    })
+    .signers([owner])
+    .rpc();
+
+  return {
+    owner,
+    tokenMint,
+    buyTokenMint,
+    sale,
+    vaultAuthority: vaultAuthorityPda(sale),
+    saleVault: vaultPda(sale, tokenMint),
+    quoteVault: vaultPda(sale, buyTokenMint),
+    solVault: solVaultPda(sale),
+    native: !!opts.native,
+    tokenProgram,
+    buyTokenProgram,
+    ownerTokenAccount,
+    ownerBuyTokenAccount,
+  };
+}
+
+export interface Buyer {
+  keypair: Keypair;
+  tokenAccount: PublicKey;
+  buyTokenAccount: PublicKey | null;
+}
+
+// 新建买家并为其发放购买代币，原生SOL销售直接使用钱包lamports
+export async function createBuyer(s: Sale, quoteAmount: bigint): Promise<Buyer> {
+  const keypair = Keypair.generate();
+  await airdrop(keypair.publicKey);
+
+  const tokenAccountAddress = await tokenAccount(s.tokenMint, keypair.publicKey, s.tokenProgram);
+  if (s.native) {
+    return { keypair, tokenAccount: tokenAccountAddress, buyTokenAccount: null };
+  }
+
+  const buyTokenAccount = await tokenAccount(s.buyTokenMint, keypair.publicKey, s.buyTokenProgram);
+  await mintTo(
+    provider().connection,
+    payer(),
+    s.buyTokenMint,
+    buyTokenAccount,
+    payer(),
+    quoteAmount,
+    [],
+    undefined,
+    s.buyTokenProgram
+  );
+
+  return { keypair, tokenAccount: tokenAccountAddress, buyTokenAccount };
+}
+
+export async function buy(s: Sale, buyer: Buyer, amount: bigint) {
+  await program.methods
+    .buyToken(new BN(amount.toString()))
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      buyTokenMint: s.buyTokenMint,
+      buyer: buyer.keypair.publicKey,
+      buyerTokenAccount: buyer.buyTokenAccount,
+      saleTokenAccount: s.quoteVault,
+      solVault: s.solVault,
+      treasury: treasuryPda(),
+      userPurchase: userPurchasePda(buyer.keypair.publicKey, s.tokenMint),
+      buyTokenProgram: s.buyTokenProgram,
+    })
+    .signers([buyer.keypair])
+    .rpc();
+}
+
+export async function finalize(s: Sale) {
+  await program.methods
+    .finalizeSale()
+    .accountsPartial({ sale: s.sale, config: configPda() })
+    .rpc();
+}
+
+export async function withdrawTokens(s: Sale, buyer: Buyer) {
+  await program.methods
+    .withdrawTokens()
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      buyTokenMint: s.buyTokenMint,
+      owner: buyer.keypair.publicKey,
+      ownerTokenAccount: buyer.tokenAccount,
+      saleTokenAccount: s.saleVault,
+      refundTokenAccount: buyer.buyTokenAccount,
+      contractTokenAccount: s.quoteVault,
+      solVault: s.solVault,
+      userPurchase: userPurchasePda(buyer.keypair.publicKey, s.tokenMint),
+      tokenProgram: s.tokenProgram,
+      buyTokenProgram: s.buyTokenProgram,
+    })
+    .signers([buyer.keypair])
+    .rpc();
+}
+
+// 默认由所有者提取，传入买家时以其身份及代币账户发起（用于校验权限）
+export async function withdrawSaleTokens(s: Sale, caller?: Buyer) {
+  const signer = caller ? caller.keypair : s.owner;
+  await program.methods
+    .withdrawSaleTokens()
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      buyTokenMint: s.buyTokenMint,
+      owner: signer.publicKey,
+      ownerTokenAccount: caller ? caller.tokenAccount : s.ownerTokenAccount,
+      saleTokenAccount: s.saleVault,
+      ownerBuyTokenAccount: caller ? caller.buyTokenAccount : s.ownerBuyTokenAccount,
+      contractTokenAccount: s.quoteVault,
+      tokenProgram: s.tokenProgram,
+      buyTokenProgram: s.buyTokenProgram,
+    })
+    .signers([signer])
+    .rpc();
+}
+
+export async function cancelSale(s: Sale) {
+  await program.methods
+    .cancelSale()
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      owner: s.owner.publicKey,
+      ownerTokenAccount: s.ownerTokenAccount,
+      saleTokenAccount: s.saleVault,
+      tokenProgram: s.tokenProgram,
+    })
+    .signers([s.owner])
+    .rpc();
+}
+
+// LP处理方式为锁定/线性释放时需传入锁仓账户
+export async function closeSale(s: Sale, lpLock: PublicKey | null = null) {
+  await program.methods
+    .closeSale()
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      buyTokenMint: s.buyTokenMint,
+      owner: s.owner.publicKey,
+      saleTokenAccount: s.saleVault,
+      contractTokenAccount: s.quoteVault,
+      ownerTokenAccount: s.ownerTokenAccount,
+      ownerBuyTokenAccount: s.ownerBuyTokenAccount,
+      solVault: s.solVault,
+      lpLock,
+      tokenProgram: s.tokenProgram,
+      buyTokenProgram: s.buyTokenProgram,
+    })
+    .signers([s.owner])
+    .rpc();
+}
+
+// 迁移至Raydium，LP处理方式为锁定/线性释放时同时传入锁仓账户
+export async function migrate(s: Sale, lockLp = false) {
+  const pool = poolAccounts(s.tokenMint, s.buyTokenMint);
+  const lpLock = lpLockPda(s.sale);
+
+  await program.methods
+    .migrateLiquidity()
+    .accountsPartial({
+      sale: s.sale,
+      pdaAccount: s.vaultAuthority,
+      config: configPda(),
+      tokenMint: s.tokenMint,
+      buyTokenMint: s.buyTokenMint,
+      payer: provider().wallet.publicKey,
+      saleTokenAccount: s.saleVault,
+      contractTokenAccount: s.quoteVault,
+      solVault: s.solVault,
+      treasury: treasuryPda(),
+      treasuryTokenAccount: getAssociatedTokenAddressSync(
+        s.buyTokenMint,
+        treasuryPda(),
+        true,
+        s.buyTokenProgram
+      ),
+      cpSwapProgram: CP_SWAP_PROGRAM_ID,
+      ammConfig: AMM_CONFIG,
+      ...pool,
+      creatorLpToken: getAssociatedTokenAddressSync(pool.lpMint, s.vaultAuthority, true),
+      lpLock: lockLp ? lpLock : null,
+      lpLockTokenAccount: lockLp ? getAssociatedTokenAddressSync(pool.lpMint, lpLock, true) : null,
+      ownerTokenAccount: s.ownerTokenAccount,
+      createPoolFee: CREATE_POOL_FEE_RECEIVER,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      saleTokenProgram: s.tokenProgram,
+      buyTokenProgram: s.buyTokenProgram,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+      rent: SYSVAR_RENT_PUBKEY,
+    })
+    .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+    .rpc();
+
+  return pool;
+}
+
+export async function releaseLp(s: Sale, lpMint: PublicKey) {
+  const lpLock = lpLockPda(s.sale);
+  const ownerLpTokenAccount = await tokenAccount(lpMint, s.owner.publicKey);
+
+  await program.methods
+    .releaseLp()
+    .accountsPartial({
+      sale: s.sale,
+      lpLock,
+      config: configPda(),
+      owner: s.owner.publicKey,
+      lpMint,
+      lpLockTokenAccount: getAssociatedTokenAddressSync(lpMint, lpLock, true),
+      ownerLpTokenAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .signers([s.owner])
+    .rpc();
+
+  return ownerLpTokenAccount;
+}
+
+export async function setPause(paused: boolean) {
+  await program.methods
+    .setPause(paused)
+    .accountsPartial({ config: configPda(), admin: provider().wallet.publicKey })
+    .rpc();
+}
+
+export async function setSalePause(s: Sale, paused: boolean) {
+  await program.methods
+    .setSalePause(paused)
+    .accountsPartial({ config: configPda(), admin: provider().wallet.publicKey, sale: s.sale })
+    .rpc();
+}
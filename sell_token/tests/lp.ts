@@ -0,0 +1,84 @@
+import { expect } from "chai";
+import { BN } from "@coral-xyz/anchor";
+import { getAssociatedTokenAddressSync, getMint } from "@solana/spl-token";
+import {
+  balance,
+  buy,
+  closeSale,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  lpLockPda,
+  migrate,
+  now,
+  program,
+  provider,
+  releaseLp,
+  sleep,
+  withdrawTokens,
+} from "./helpers";
+
+describe("lp", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("burns every LP token minted to the sale", async () => {
+    const s = await createSale({ duration: 2 });
+    const buyer = await createBuyer(s, 500_000_000n);
+    await buy(s, buyer, 500_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+    const pool = await migrate(s);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.lpBurned.toNumber()).to.be.greaterThan(0);
+
+    const creatorLpToken = getAssociatedTokenAddressSync(pool.lpMint, s.vaultAuthority, true);
+    expect(await balance(creatorLpToken)).to.equal(0n);
+    expect((await getMint(provider().connection, pool.lpMint)).supply).to.equal(0n);
+  });
+
+  it("locks LP until the unlock time and releases it to the owner", async () => {
+    const s = await createSale({
+      duration: 2,
+      lpPolicy: { lockUntil: { unlockTime: new BN(now() + 15) } },
+    });
+    const buyer = await createBuyer(s, 500_000_000n);
+    await buy(s, buyer, 500_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+    const pool = await migrate(s, true);
+
+    const lpLock = await program.account.lpLock.fetch(lpLockPda(s.sale));
+    const locked = BigInt(lpLock.totalAmount.toString());
+    expect(locked > 0n).to.be.true;
+    expect(lpLock.lpMint.toBase58()).to.equal(pool.lpMint.toBase58());
+
+    try {
+      await releaseLp(s, pool.lpMint);
+      expect.fail("release should fail");
+    } catch (err) {
+      expect(String(err)).to.include("LpLocked");
+    }
+
+    // 锁定的LP未提取前不能关闭销售
+    await withdrawTokens(s, buyer);
+    try {
+      await closeSale(s, lpLockPda(s.sale));
+      expect.fail("close should fail");
+    } catch (err) {
+      expect(String(err)).to.include("LpLocked");
+    }
+
+    await sleep(12000);
+    const ownerLpTokenAccount = await releaseLp(s, pool.lpMint);
+    expect(await balance(ownerLpTokenAccount)).to.equal(locked);
+
+    await closeSale(s, lpLockPda(s.sale));
+    expect(await program.account.saleAccount.fetchNullable(s.sale)).to.be.null;
+  });
+});
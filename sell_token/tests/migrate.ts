@@ -0,0 +1,73 @@
+import { expect } from "chai";
+import { getMint, mintTo } from "@solana/spl-token";
+import {
+  Buyer,
+  balance,
+  buy,
+  closeSale,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  migrate,
+  payer,
+  program,
+  provider,
+  sleep,
+  withdrawSaleTokens,
+  withdrawTokens,
+} from "./helpers";
+
+describe("migrate", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("migrates a sold-out sale, then buyers claim and the owner withdraws", async () => {
+    // 默认销售 50 万代币，每个完整代币 1000 个购买代币最小单位，全部售出需 5 亿
+    const s = await createSale({ duration: 3 });
+    const amounts = [300_000_000n, 200_000_000n];
+
+    const buyers: Buyer[] = [];
+    for (const amount of amounts) {
+      const buyer = await createBuyer(s, amount);
+      await buy(s, buyer, amount);
+      buyers.push(buyer);
+    }
+
+    await sleep(5000);
+    await finalize(s);
+    const pool = await migrate(s);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.status).to.deep.equal({ migrated: {} });
+    expect(sale.poolState.toBase58()).to.equal(pool.poolState.toBase58());
+
+    // 募集资金全部注入流动性，配对数量按销售价格换算
+    expect(await balance(s.quoteVault)).to.equal(0n);
+    expect(await balance(pool.buyTokenVault)).to.equal(500_000_000n);
+    expect(await balance(pool.saleTokenVault)).to.equal(500_000_000_000n);
+    // 买家的代币留在托管账户中等待领取
+    expect(await balance(s.saleVault)).to.equal(500_000_000_000n);
+
+    // 铸币及冻结权限已撤销，代币总量固定
+    const mint = await getMint(provider().connection, s.tokenMint, undefined, s.tokenProgram);
+    expect(mint.mintAuthority).to.be.null;
+    expect(mint.freezeAuthority).to.be.null;
+
+    for (const [i, buyer] of buyers.entries()) {
+      await withdrawTokens(s, buyer);
+      expect(await balance(buyer.tokenAccount)).to.equal(amounts[i] * 1_000n);
+    }
+    expect(await balance(s.saleVault)).to.equal(0n);
+
+    // 迁移后转入的购买代币由所有者提取
+    const conn = provider().connection;
+    await mintTo(conn, payer(), s.buyTokenMint, s.quoteVault, payer(), 7n);
+    await withdrawSaleTokens(s);
+    expect(await balance(s.ownerBuyTokenAccount)).to.equal(7n);
+
+    await closeSale(s);
+    expect(await program.account.saleAccount.fetchNullable(s.sale)).to.be.null;
+  });
+});
@@ -0,0 +1,87 @@
+import { expect } from "chai";
+import {
+  balance,
+  buy,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  setPause,
+  setSalePause,
+  sleep,
+  withdrawTokens,
+} from "./helpers";
+
+describe("pause", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  afterEach(async () => {
+    await setPause(false);
+    await ensureConfig();
+  });
+
+  it("blocks purchases while the platform is paused", async () => {
+    const s = await createSale();
+    const buyer = await createBuyer(s, 2_000_000n);
+
+    await setPause(true);
+    try {
+      await buy(s, buyer, 1_000_000n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("Paused");
+    }
+
+    await setPause(false);
+    await buy(s, buyer, 1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(1_000_000n);
+  });
+
+  it("blocks purchases of a paused sale only", async () => {
+    const paused = await createSale();
+    const open = await createSale();
+    const first = await createBuyer(paused, 1_000_000n);
+    const second = await createBuyer(open, 1_000_000n);
+
+    await setSalePause(paused, true);
+    try {
+      await buy(paused, first, 1_000_000n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("Paused");
+    }
+
+    await buy(open, second, 1_000_000n);
+    expect(await balance(open.quoteVault)).to.equal(1_000_000n);
+  });
+
+  it("keeps refunds open only when configured", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const first = await createBuyer(s, 1_000_000n);
+    const second = await createBuyer(s, 1_000_000n);
+    await buy(s, first, 1_000_000n);
+    await buy(s, second, 1_000_000n);
+
+    await sleep(4000);
+    await setSalePause(s, true);
+
+    // 默认配置暂停期间仍可结算和退款
+    await finalize(s);
+    await withdrawTokens(s, first);
+    expect(await balance(first.buyTokenAccount)).to.equal(1_000_000n);
+
+    await ensureConfig({ withdrawWhenPaused: false });
+    try {
+      await withdrawTokens(s, second);
+      expect.fail("refund should fail");
+    } catch (err) {
+      expect(String(err)).to.include("Paused");
+    }
+
+    await setSalePause(s, false);
+    await withdrawTokens(s, second);
+    expect(await balance(second.buyTokenAccount)).to.equal(1_000_000n);
+  });
+});
@@ -0,0 +1,118 @@
+import { expect } from "chai";
+import {
+  balance,
+  buy,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  program,
+  userPurchasePda,
+} from "./helpers";
+
+interface PriceCase {
+  name: string;
+  decimals: number;
+  quoteDecimals: number;
+  priceNumerator: bigint;
+  priceDenominator: bigint;
+  budget: bigint;
+  tokens: bigint;
+  cost: bigint;
+}
+
+const cases: PriceCase[] = [
+  {
+    // 每 2 个完整代币仅 1 个购买代币最小单位，低于旧整数价格的最小粒度
+    name: "a price below one quote base unit per token",
+    decimals: 9,
+    quoteDecimals: 6,
+    priceNumerator: 1n,
+    priceDenominator: 2_000_000_000n,
+    budget: 3n,
+    tokens: 6_000_000_000n,
+    cost: 3n,
+  },
+  {
+    name: "a fractional price with dust left to the buyer",
+    decimals: 6,
+    quoteDecimals: 9,
+    priceNumerator: 5n,
+    priceDenominator: 2n,
+    budget: 11n,
+    tokens: 4n,
+    cost: 10n,
+  },
+  {
+    name: "an indivisible token priced in a high precision quote",
+    decimals: 0,
+    quoteDecimals: 9,
+    priceNumerator: 1_500_000_000n,
+    priceDenominator: 1n,
+    budget: 4_000_000_000n,
+    tokens: 2n,
+    cost: 3_000_000_000n,
+  },
+  {
+    name: "a quote without decimals",
+    decimals: 9,
+    quoteDecimals: 0,
+    priceNumerator: 1n,
+    priceDenominator: 1_000_000_000n,
+    budget: 7n,
+    tokens: 7_000_000_000n,
+    cost: 7n,
+  },
+  {
+    // 成本向上取整：4 个最小单位价值 12/7，需支付 2
+    name: "a cost that rounds up in favour of the sale",
+    decimals: 6,
+    quoteDecimals: 6,
+    priceNumerator: 3n,
+    priceDenominator: 7n,
+    budget: 2n,
+    tokens: 4n,
+    cost: 2n,
+  },
+];
+
+describe("price", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  for (const c of cases) {
+    it(`buys with ${c.name}`, async () => {
+      const s = await createSale({
+        decimals: c.decimals,
+        quoteDecimals: c.quoteDecimals,
+        priceNumerator: c.priceNumerator,
+        priceDenominator: c.priceDenominator,
+      });
+      const buyer = await createBuyer(s, c.budget);
+
+      await buy(s, buyer, c.budget);
+
+      const purchase = await program.account.userPurchase.fetch(
+        userPurchasePda(buyer.keypair.publicKey, s.tokenMint)
+      );
+      expect(BigInt(purchase.tokenAmount.toString())).to.equal(c.tokens);
+      expect(BigInt(purchase.purchaseAmount.toString())).to.equal(c.cost);
+
+      // 不足以换取一个代币最小单位的零头不会被扣除
+      expect(await balance(buyer.buyTokenAccount)).to.equal(c.budget - c.cost);
+      expect(await balance(s.quoteVault)).to.equal(c.cost);
+    });
+  }
+
+  it("rejects a budget too small for one token base unit", async () => {
+    const s = await createSale({ priceNumerator: 3n, priceDenominator: 1n });
+    const buyer = await createBuyer(s, 2n);
+
+    try {
+      await buy(s, buyer, 2n);
+      expect.fail("buy should fail");
+    } catch (err) {
+      expect(String(err)).to.include("AmountTooSmall");
+    }
+  });
+});
@@ -0,0 +1,109 @@
+import { expect } from "chai";
+import { Keypair } from "@solana/web3.js";
+import {
+  balance,
+  buy,
+  closeSale,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  program,
+  provider,
+  sleep,
+  userPurchasePda,
+  withdrawSaleTokens,
+  withdrawTokens,
+} from "./helpers";
+
+// 转账手续费 1%（100 个基点）
+const afterFee = (amount: bigint) => amount - amount / 100n;
+
+describe("transfer fee", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("books the net quote amount received by the vault", async () => {
+    const s = await createSale({
+      buyTokenExtensions: { transferFeeBps: 100 },
+      softCap: 500_000_000_000n,
+      duration: 2,
+    });
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+
+    const purchase = await program.account.userPurchase.fetch(
+      userPurchasePda(buyer.keypair.publicKey, s.tokenMint)
+    );
+    expect(BigInt(purchase.purchaseAmount.toString())).to.equal(afterFee(1_000_000n));
+    expect(await balance(s.quoteVault)).to.equal(afterFee(1_000_000n));
+
+    await sleep(4000);
+    await finalize(s);
+
+    // 退款按实际到账数量从托管账户转出，转出时再次扣除手续费
+    await withdrawTokens(s, buyer);
+    expect(await balance(s.quoteVault)).to.equal(0n);
+    expect(await balance(buyer.buyTokenAccount)).to.equal(afterFee(afterFee(1_000_000n)));
+  });
+
+  it("returns the net deposit when a sale token with transfer fee fails", async () => {
+    const s = await createSale({
+      tokenExtensions: { transferFeeBps: 100 },
+      softCap: 400_000_000_000n,
+      duration: 2,
+    });
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    const deposited = afterFee(1_000_000_000_000n);
+    expect(BigInt(sale.depositedAmount.toString())).to.equal(deposited);
+    expect(await balance(s.saleVault)).to.equal(deposited);
+
+    await sleep(4000);
+    await finalize(s);
+    await withdrawSaleTokens(s);
+
+    expect(await balance(s.saleVault)).to.equal(0n);
+    expect(await balance(s.ownerTokenAccount)).to.equal(afterFee(deposited));
+  });
+
+  it("closes a sale whose vaults hold withheld transfer fees", async () => {
+    const s = await createSale({
+      tokenExtensions: { transferFeeBps: 100 },
+      buyTokenExtensions: { transferFeeBps: 100 },
+      softCap: 400_000_000_000n,
+      duration: 2,
+    });
+    const buyer = await createBuyer(s, 1_000_000n);
+    await buy(s, buyer, 1_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+    await withdrawTokens(s, buyer);
+    await withdrawSaleTokens(s);
+
+    // 两个托管账户均为空，但各自仍扣留着转入时的手续费
+    expect(await balance(s.saleVault)).to.equal(0n);
+    expect(await balance(s.quoteVault)).to.equal(0n);
+
+    await closeSale(s);
+
+    const conn = provider().connection;
+    expect(await program.account.saleAccount.fetchNullable(s.sale)).to.be.null;
+    expect(await conn.getAccountInfo(s.saleVault)).to.be.null;
+    expect(await conn.getAccountInfo(s.quoteVault)).to.be.null;
+  });
+
+  it("rejects mints with a permanent delegate", async () => {
+    try {
+      await createSale({
+        buyTokenExtensions: { permanentDelegate: Keypair.generate().publicKey },
+      });
+      expect.fail("init should fail");
+    } catch (err) {
+      expect(String(err)).to.include("UnsupportedMintExtension");
+    }
+  });
+});
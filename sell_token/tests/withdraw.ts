@@ -0,0 +1,254 @@
+import { expect } from "chai";
+import { TOKEN_2022_PROGRAM_ID, getAssociatedTokenAddressSync, mintTo } from "@solana/spl-token";
+import {
+  balance,
+  buy,
+  cancelSale,
+  closeSale,
+  createBuyer,
+  createSale,
+  ensureConfig,
+  finalize,
+  payer,
+  program,
+  provider,
+  sleep,
+  treasuryPda,
+  withdrawSaleTokens,
+  withdrawTokens,
+} from "./helpers";
+
+describe("withdraw", () => {
+  before(async () => {
+    await ensureConfig();
+  });
+
+  it("rejects claims before liquidity is migrated", async () => {
+    const s = await createSale();
+    const buyer = await createBuyer(s, 500_000_000n);
+
+    await buy(s, buyer, 500_000_000n);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.status).to.deep.equal({ succeeded: {} });
+
+    try {
+      await withdrawTokens(s, buyer);
+      expect.fail("claim should fail");
+    } catch (err) {
+      expect(String(err)).to.include("InvalidSaleStatus");
+    }
+
+    expect(await balance(buyer.tokenAccount)).to.equal(0n);
+    expect(await balance(s.saleVault)).to.equal(1_000_000_000_000n);
+  });
+
+  it("refunds buyers and returns the supply when the soft cap is missed", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+    expect(await balance(buyer.buyTokenAccount)).to.equal(0n);
+
+    await sleep(4000);
+    await finalize(s);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.status).to.deep.equal({ failed: {} });
+
+    await withdrawTokens(s, buyer);
+    expect(await balance(buyer.buyTokenAccount)).to.equal(1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(0n);
+
+    await withdrawSaleTokens(s);
+    expect(await balance(s.ownerTokenAccount)).to.equal(1_000_000_000_000n);
+    expect(await balance(s.saleVault)).to.equal(0n);
+  });
+
+  it("rejects withdrawing sale tokens by anyone but the owner", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const attacker = await createBuyer(s, 0n);
+
+    await sleep(4000);
+    await finalize(s);
+
+    try {
+      await withdrawSaleTokens(s, attacker);
+      expect.fail("withdraw should fail");
+    } catch (err) {
+      expect(String(err)).to.include("Unauthorized");
+    }
+
+    expect(await balance(s.saleVault)).to.equal(1_000_000_000_000n);
+    expect(await balance(attacker.tokenAccount)).to.equal(0n);
+  });
+
+  it("escrows refundable mint fees in the sale vault until settlement", async () => {
+    await ensureConfig({ mintFeeBps: 100, mintFeeRefundable: true });
+
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const buyer = await createBuyer(s, 1_000_000n);
+    const treasuryTokenAccount = getAssociatedTokenAddressSync(
+      s.buyTokenMint,
+      treasuryPda(),
+      true
+    );
+
+    // 预算 1_000_000 扣除 1% 手续费后支付 990_000，手续费 9_900 留在销售金库
+    await buy(s, buyer, 1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(999_900n);
+    expect(await balance(treasuryTokenAccount)).to.equal(0n);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.escrowedFees.toNumber()).to.equal(9_900);
+
+    await sleep(4000);
+    await finalize(s);
+    await withdrawTokens(s, buyer);
+
+    expect(await balance(buyer.buyTokenAccount)).to.equal(1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(0n);
+
+    await ensureConfig();
+  });
+
+  it("returns the supply to the owner on cancel", async () => {
+    const s = await createSale();
+
+    await cancelSale(s);
+
+    const sale = await program.account.saleAccount.fetch(s.sale);
+    expect(sale.status).to.deep.equal({ cancelled: {} });
+    expect(await balance(s.ownerTokenAccount)).to.equal(1_000_000_000_000n);
+    expect(await balance(s.saleVault)).to.equal(0n);
+  });
+
+  it("refunds buyers of a cancelled sale", async () => {
+    await ensureConfig({ cancelWithPurchases: true });
+
+    const s = await createSale();
+    const buyer = await createBuyer(s, 2_000_000n);
+
+    await buy(s, buyer, 2_000_000n);
+    await cancelSale(s);
+    await withdrawTokens(s, buyer);
+
+    expect(await balance(buyer.buyTokenAccount)).to.equal(2_000_000n);
+    expect(await balance(buyer.tokenAccount)).to.equal(0n);
+
+    await ensureConfig();
+  });
+
+  it("rejects cancelling a sale with purchases by default", async () => {
+    const s = await createSale();
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+
+    try {
+      await cancelSale(s);
+      expect.fail("cancel should fail");
+    } catch (err) {
+      expect(String(err)).to.include("SaleHasPurchases");
+    }
+  });
+
+  it("closes a settled sale once every purchase is refunded", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+    await sleep(4000);
+    await finalize(s);
+    await withdrawSaleTokens(s);
+
+    try {
+      await closeSale(s);
+      expect.fail("close should fail");
+    } catch (err) {
+      expect(String(err)).to.include("OpenPositions");
+    }
+
+    await withdrawTokens(s, buyer);
+    await closeSale(s);
+
+    const conn = provider().connection;
+    expect(await program.account.saleAccount.fetchNullable(s.sale)).to.be.null;
+    expect(await conn.getAccountInfo(s.saleVault)).to.be.null;
+    expect(await conn.getAccountInfo(s.quoteVault)).to.be.null;
+  });
+
+  it("sweeps donated vault balances to the owner on close", async () => {
+    const s = await createSale({ softCap: 500_000_000_000n, duration: 2 });
+
+    await sleep(4000);
+    await finalize(s);
+    await withdrawSaleTokens(s);
+
+    // 失败销售的购买代币金库没有提取途径，他人转入的代币不能阻塞关闭
+    const conn = provider().connection;
+    await mintTo(conn, payer(), s.buyTokenMint, s.quoteVault, payer(), 5n);
+    await closeSale(s);
+
+    expect(await balance(s.ownerBuyTokenAccount)).to.equal(5n);
+    expect(await conn.getAccountInfo(s.quoteVault)).to.be.null;
+  });
+
+  it("refunds native SOL buyers in lamports", async () => {
+    const s = await createSale({ native: true, softCap: 500_000_000_000n, duration: 2 });
+    const buyer = await createBuyer(s, 0n);
+    const conn = provider().connection;
+    const vaultBefore = await conn.getBalance(s.solVault);
+
+    await buy(s, buyer, 1_000_000n);
+    expect(await conn.getBalance(s.solVault)).to.equal(vaultBefore + 1_000_000);
+
+    await sleep(4000);
+    await finalize(s);
+
+    const lamportsBefore = await conn.getBalance(buyer.keypair.publicKey);
+    await withdrawTokens(s, buyer);
+
+    expect(await conn.getBalance(buyer.keypair.publicKey)).to.be.greaterThan(
+      lamportsBefore + 1_000_000
+    );
+    expect(await conn.getBalance(s.solVault)).to.equal(vaultBefore);
+  });
+
+  it("charges native mint fees below the rent minimum into the treasury", async () => {
+    await ensureConfig({ mintFeeBps: 100 });
+
+    const s = await createSale({ native: true });
+    const buyer = await createBuyer(s, 0n);
+    const conn = provider().connection;
+    const treasuryBefore = await conn.getBalance(treasuryPda());
+
+    // 手续费 9_900 lamports 低于免租最低余额，金库已预存该余额故仍可转入
+    await buy(s, buyer, 1_000_000n);
+    expect(await conn.getBalance(treasuryPda())).to.equal(treasuryBefore + 9_900);
+
+    await ensureConfig();
+  });
+
+  it("refunds buyers and returns the supply with Token-2022 mints", async () => {
+    const s = await createSale({
+      tokenProgram: TOKEN_2022_PROGRAM_ID,
+      buyTokenProgram: TOKEN_2022_PROGRAM_ID,
+      softCap: 500_000_000_000n,
+      duration: 2,
+    });
+    const buyer = await createBuyer(s, 1_000_000n);
+
+    await buy(s, buyer, 1_000_000n);
+    expect(await balance(s.quoteVault)).to.equal(1_000_000n);
+
+    await sleep(4000);
+    await finalize(s);
+
+    await withdrawTokens(s, buyer);
+    expect(await balance(buyer.buyTokenAccount)).to.equal(1_000_000n);
+
+    await withdrawSaleTokens(s);
+    expect(await balance(s.ownerTokenAccount)).to.equal(1_000_000_000_000n);
+  });
+});